@@ -10,11 +10,24 @@ mod channel_header;
 mod chat;
 mod create_or_join;
 mod current_user;
+mod direct_messages;
+mod directory;
+mod group_header;
 mod home;
+mod invite_page;
+mod invite_panel;
 mod joined_channels;
 mod login;
 mod logout;
+mod members_panel;
+mod mention_picker;
+mod mention_toasts;
+mod message_bubble;
+mod message_list;
 mod register;
+mod room_settings;
+mod thread_panel;
+mod typing_indicator;
 
 pub enum AppPath {
     Register,
@@ -23,6 +36,8 @@ pub enum AppPath {
     Home,
     Profile(String),
     Channel(Option<String>),
+    Directory,
+    Invite(String),
 }
 
 impl std::fmt::Display for AppPath {
@@ -37,6 +52,8 @@ impl std::fmt::Display for AppPath {
                 Some(id) => write!(f, "channel/{}", id),
                 None => write!(f, "channel"),
             },
+            Self::Directory => write!(f, "channel/directory"),
+            Self::Invite(code) => write!(f, "/invite/{}", code),
         }
     }
 }
@@ -49,18 +66,20 @@ impl leptos_router::ToHref for AppPath {
 
 #[server(AuthenticateUser)]
 async fn authenticate_user() -> Result<bool, ServerFnError> {
-    use crate::{
-        models::user_model::UserData,
-        state::{auth, pool},
-    };
+    use crate::state::{auth, repository};
 
     let auth = auth()?;
-    let pool = pool()?;
+    let repository = repository()?;
     if auth.is_authenticated() {
         let user = auth
             .current_user
             .ok_or_else(|| ServerFnError::new("There is no current user!"))?;
-        if UserData::get_from_uuid(&user.uuid, &pool).await.is_none() {
+        if repository
+            .users
+            .get_user_by_uuid(&user.uuid)
+            .await?
+            .is_none()
+        {
             return Err(ServerFnError::new("Invalid user"));
         }
         Ok(true)
@@ -115,6 +134,7 @@ pub fn App() -> impl IntoView {
                         condition=move || is_auth.get()
                         view=move || view! { <chat::ChatPage logout_action/> }
                     >
+                        <Route path="directory" view=directory::Directory/>
                         <Route path=":id" view=channel::Channel/>
                         <Route path="" view=|| view! {
                             <div class="h-full grow flex items-center justify-center">
@@ -122,6 +142,20 @@ pub fn App() -> impl IntoView {
                             </div>
                         }/>
                     </ProtectedRoute>
+                    <Route
+                        path="/invite/:code"
+                        view=move || view! {
+                            <Transition fallback=|| view! { <p>"Loading..."</p> }>
+                                {move || auth_resource.map(|res| {
+                                    let val = matches!(res, Ok(true));
+                                    if is_auth.get() != val {
+                                        set_is_auth.set(val);
+                                    }
+                                    view! { <invite_page::InvitePage is_auth=val/> }
+                                })}
+                            </Transition>
+                        }
+                    />
                     <Route path=AppPath::Register view=register::RegisterPage/>
                     <Route path=AppPath::Login view=move || view! { <login::LoginPage login_action/> }/>
                 </Routes>
@@ -1,23 +1,40 @@
 use {
-    super::{app_error::ErrorTemplate, channel_header::ChannelHeader, chat::WebsocketCtx},
+    super::{
+        app_error::ErrorTemplate,
+        channel_header::ChannelHeader,
+        chat::WebsocketCtx,
+        group_header::GroupHeader,
+        members_panel::MembersPanel,
+        mention_picker::{
+            complete_mention, pending_mention, place_caret_at_end, suggestions, MentionPicker,
+        },
+        message_list::MessageList,
+        thread_panel::ThreadCtx,
+        typing_indicator::{TypingIndicator, TypingNotifier},
+    },
     crate::{
         error::AppError,
         models::{
-            message_model::{MsgResponse, WsPayload},
+            frame_model::{ClientFrame, ServerFrame},
+            message_model::{MsgCursor, MsgPage, MsgResponse},
+            read_marker_model::ReadReceipt,
+            role_model::Role,
             user_model::User,
         },
-        state::rooms_manager::RoomData,
+        state::rooms_manager::{RoomData, RoomKind},
     },
-    chrono::Local,
     leptos::*,
+    std::collections::HashMap,
 };
 
+pub const PAGE_SIZE: usize = 30;
+
 #[server]
 async fn validate_path(path: String) -> Result<RoomData, ServerFnError> {
-    use crate::state::{auth, pool, rooms_manager};
+    use crate::state::{auth, repository, rooms_manager};
 
     let auth = auth()?;
-    let pool = pool()?;
+    let repository = repository()?;
     let rooms_manager = rooms_manager()?;
     if !auth.is_authenticated() {
         return Err(ServerFnError::new(
@@ -28,8 +45,12 @@ async fn validate_path(path: String) -> Result<RoomData, ServerFnError> {
         let room_uuid = path
             .strip_prefix("/channel/")
             .expect("Valid uuid is needed");
+        let user = auth
+            .current_user
+            .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+        // the room data lists every member, only those already in the room get to see it
         rooms_manager
-            .validate_uuid(room_uuid, &pool)
+            .member_room(room_uuid, &user.uuid, &repository)
             .await
             .map_err(|err| ServerFnError::new(err))
     } else {
@@ -37,42 +58,99 @@ async fn validate_path(path: String) -> Result<RoomData, ServerFnError> {
     }
 }
 
-#[server(PublishMsg)]
-async fn publish_msg(text: String, room_uuid: String) -> Result<(), ServerFnError> {
-    use crate::models::message_model::MsgData;
-    use crate::state::{auth, pool};
-    use chrono::Utc;
+#[server(FetchMsgPage, "/api", "GetJson")]
+pub async fn fetch_msg_page(
+    room_uuid: String,
+    parent: Option<String>,
+    before: Option<MsgCursor>,
+    after: Option<MsgCursor>,
+    limit: usize,
+) -> Result<MsgPage, ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
 
     let auth = auth()?;
-    let pool = pool()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
     let user = auth
         .current_user
         .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
-    let created_at = Utc::now();
-    let msg_data = MsgData::new(room_uuid.clone(), user.uuid, text, created_at);
-    msg_data
-        .insert_into_db(&pool)
+    rooms_manager
+        .member_room(&room_uuid, &user.uuid, &repository)
         .await
-        .map_err(|err| ServerFnError::new(err))
+        .map_err(|err| ServerFnError::new(err))?;
+
+    MsgResponse::get_page(
+        &room_uuid,
+        parent.as_deref(),
+        before.as_ref(),
+        after.as_ref(),
+        limit,
+        &repository,
+    )
+    .await
+    .map_err(|err| ServerFnError::new(err))
 }
 
-#[server(FetchMsg, "/api", "GetJson")]
-async fn fetch_msg(room_uuid: String) -> Result<Vec<MsgResponse>, ServerFnError> {
-    use crate::models::message_model::MsgResponse;
-    use crate::state::pool;
+#[server(FetchReadMarker, "/api", "GetJson")]
+pub async fn fetch_read_marker(room_uuid: String) -> Result<Option<MsgCursor>, ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
 
-    let pool = pool()?;
-    let room_uuid = room_uuid
-        .strip_prefix("/channel/")
-        .ok_or_else(|| ServerFnError::new("Invalid path"))?
-        .to_string();
-    match MsgResponse::get_all_msg(&room_uuid, &pool).await {
-        Ok(mut vec_msg) => {
-            vec_msg.sort();
-            Ok(vec_msg)
-        }
-        Err(err) => Err(ServerFnError::new(err)),
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    rooms_manager
+        .member_room(&room_uuid, &user.uuid, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    Ok(repository
+        .read_markers
+        .get_read_marker(&user.uuid, &room_uuid)
+        .await?
+        .map(|read_marker| read_marker.cursor()))
+}
+
+#[server(FetchReadReceipts, "/api", "GetJson")]
+pub async fn fetch_read_receipts(room_uuid: String) -> Result<Vec<ReadReceipt>, ServerFnError> {
+    use crate::{
+        models::user_model::User,
+        state::{auth, repository, rooms_manager},
+    };
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let room_data = rooms_manager
+        .member_room(&room_uuid, &user.uuid, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    if !room_data.read_receipts {
+        return Ok(Vec::new());
     }
+
+    // --- only current members other than the asking user
+    let vec_receipt = repository
+        .read_markers
+        .get_room_read_markers(&room_uuid)
+        .await?
+        .into_iter()
+        .filter(|read_marker| read_marker.user_uuid != user.uuid)
+        .filter_map(|read_marker| {
+            room_data
+                .users
+                .get(&read_marker.user_uuid)
+                .map(|member: &User| ReadReceipt {
+                    user: member.clone(),
+                    cursor: read_marker.cursor(),
+                })
+        })
+        .collect();
+    Ok(vec_receipt)
 }
 
 #[component]
@@ -88,14 +166,177 @@ pub fn Channel() -> impl IntoView {
             {move || {
                 match path_resource.get().unwrap_or(Err(ServerFnError::new("Invalid path"))) {
                     Ok(room) => {
-                        let WebsocketCtx { send, message_bytes } = expect_context::<WebsocketCtx>();
-                        // i suspect multiple path.get() causes fetch_msg to be called multiple times
-                        let msg_resource = create_resource(move || path.get(), fetch_msg);
+                        let ws_ctx = expect_context::<WebsocketCtx>();
+                        let frame = ws_ctx.frame;
+                        let room_uuid = room.room_uuid.clone();
+                        let msg_resource = create_resource(
+                            move || room_uuid.clone(),
+                            |room_uuid| fetch_msg_page(room_uuid, None, None, None, PAGE_SIZE),
+                        );
+                        // --- the resource only seeds the list, new messages are pushed by the server
+                        let messages = create_rw_signal(Vec::<MsgResponse>::new());
+                        let has_more = create_rw_signal(false);
+                        let loading = create_rw_signal(false);
+                        create_effect(move |_| {
+                            if let Some(Ok(page)) = msg_resource.get() {
+                                has_more.set(page.has_more);
+                                messages.set(page.messages);
+                            }
+                        });
+                        // --- the marker is read once so the divider stays where it was when the channel opened
+                        let room_uuid = room.room_uuid.clone();
+                        let marker_resource = create_resource(move || room_uuid.clone(), fetch_read_marker);
+                        let first_unread = create_rw_signal(None::<String>);
+                        let divider_placed = store_value(false);
+                        create_effect(move |_| {
+                            if divider_placed.get_value() {
+                                return;
+                            }
+                            let (Some(Ok(read_marker)), Some(Ok(page))) = (marker_resource.get(), msg_resource.get()) else {
+                                return;
+                            };
+                            let current_uuid = user_resource.get().and_then(Result::ok).map(|user| user.uuid).unwrap_or_default();
+                            first_unread.set(
+                                page.messages
+                                    .iter()
+                                    .filter(|msg| msg.msg_sender.as_ref().map(|user| &user.uuid) != Some(&current_uuid))
+                                    .find(|msg| read_marker.as_ref().is_none_or(|cursor| &MsgCursor::from_msg(msg) > cursor))
+                                    .map(|msg| msg.msg_uuid.clone()),
+                            );
+                            divider_placed.set_value(true);
+                        });
+                        // --- viewing the channel reads everything up to the latest message,
+                        // a fresh handshake acknowledges again since frames sent before it are dropped
+                        let room_uuid = room.room_uuid.clone();
+                        let ack_ctx = ws_ctx.clone();
+                        create_effect(move |last_acked: Option<Option<MsgCursor>>| {
+                            let last_acked = last_acked.flatten();
+                            let welcomed = matches!(frame.get(), Some(ServerFrame::Welcome { .. }));
+                            if marker_resource.get().is_none() {
+                                return last_acked;
+                            }
+                            let latest = messages.with(|vec| vec.last().map(MsgCursor::from_msg));
+                            match latest {
+                                Some(cursor) if welcomed || last_acked.as_ref().is_none_or(|acked| &cursor > acked) => {
+                                    ack_ctx.send_frame(&ClientFrame::Ack {
+                                        room_uuid: room_uuid.clone(),
+                                        cursor: cursor.clone(),
+                                    });
+                                    Some(cursor)
+                                }
+                                _ => last_acked,
+                            }
+                        });
+                        // --- how far the other members have read, by user uuid
+                        let read_receipts = create_rw_signal(room.read_receipts);
+                        let room_uuid = room.room_uuid.clone();
+                        let receipts_resource = create_resource(
+                            move || (room_uuid.clone(), read_receipts.get()),
+                            |(room_uuid, enabled)| async move {
+                                if enabled {
+                                    fetch_read_receipts(room_uuid).await
+                                } else {
+                                    Ok(Vec::new())
+                                }
+                            },
+                        );
+                        let receipts = create_rw_signal(HashMap::<String, ReadReceipt>::new());
+                        create_effect(move |_| {
+                            if let Some(Ok(vec_receipt)) = receipts_resource.get() {
+                                receipts.set(
+                                    vec_receipt
+                                        .into_iter()
+                                        .map(|receipt| (receipt.user.uuid.clone(), receipt))
+                                        .collect(),
+                                );
+                            }
+                        });
+                        // the latest message of the current user and who has read past it
+                        let seen_by = create_memo(move |_| {
+                            let current_uuid = user_resource.get().and_then(Result::ok).map(|user| user.uuid).unwrap_or_default();
+                            let latest_own = messages.with(|vec| {
+                                vec.iter()
+                                    .rev()
+                                    .find(|msg| !msg.is_deleted() && msg.msg_sender.as_ref().is_some_and(|user| user.uuid == current_uuid))
+                                    .map(MsgCursor::from_msg)
+                            })?;
+                            let users = receipts.with(|receipts| {
+                                receipts
+                                    .values()
+                                    .filter(|receipt| receipt.cursor >= latest_own)
+                                    .map(|receipt| receipt.user.clone())
+                                    .collect::<Vec<User>>()
+                            });
+                            Some((latest_own.msg_uuid, users))
+                        });
+                        let room_uuid = room.room_uuid.clone();
+                        let load_older = Callback::new(move |cursor: MsgCursor| {
+                            loading.set(true);
+                            let room_uuid = room_uuid.clone();
+                            spawn_local(async move {
+                                match fetch_msg_page(room_uuid, None, Some(cursor), None, PAGE_SIZE).await {
+                                    Ok(page) => {
+                                        has_more.set(page.has_more);
+                                        messages.update(|vec| {
+                                            let mut older = page.messages;
+                                            older.retain(|msg| !vec.iter().any(|known| known.msg_uuid == msg.msg_uuid));
+                                            older.append(vec);
+                                            *vec = older;
+                                        });
+                                    }
+                                    Err(err) => logging::warn!("unable to load older messages: {}", err),
+                                }
+                                loading.set(false);
+                            });
+                        });
                         let message_input = create_node_ref::<html::Div>();
-                        let publish_msg = create_server_action::<PublishMsg>();
+                        let typing_notifier = TypingNotifier::new(room.room_uuid.clone());
+                        let typing_users = create_rw_signal(Vec::<User>::new());
+                        {
+                            let ws_ctx = ws_ctx.clone();
+                            on_cleanup(move || typing_notifier.stop(&ws_ctx));
+                        }
+                        // --- mention autocomplete from the members of the room
+                        let members = room.users.values().cloned().collect::<Vec<User>>();
+                        let mention_prefix = create_rw_signal(None::<String>);
+                        let mention_suggestions = create_memo(move |_| {
+                            let current_uuid = user_resource.get().and_then(Result::ok).map(|user| user.uuid).unwrap_or_default();
+                            mention_prefix
+                                .get()
+                                .map(|prefix| {
+                                    let mut vec_user = suggestions(&members, &prefix);
+                                    vec_user.retain(|user| user.uuid != current_uuid);
+                                    vec_user
+                                })
+                                .unwrap_or_default()
+                        });
+                        let pick_mention = Callback::new(move |user: User| {
+                            if let Some(node) = message_input.get() {
+                                node.set_inner_text(&complete_mention(&node.inner_text(), &user.user_name));
+                                place_caret_at_end(&node);
+                            }
+                            mention_prefix.set(None);
+                        });
+                        let typing_ctx = ws_ctx.clone();
+                        let handle_keydown = move |ev: ev::KeyboardEvent| {
+                            if ev.key() == "Tab" {
+                                if let Some(user) = mention_suggestions.get_untracked().first() {
+                                    ev.prevent_default();
+                                    pick_mention.call(user.clone());
+                                    return;
+                                }
+                            }
+                            if ev.key() != "Enter" || ev.shift_key() {
+                                typing_notifier.keystroke(&typing_ctx);
+                            }
+                        };
                         let handle_keyup = move |ev: ev::KeyboardEvent| {
                             ev.prevent_default();
+                            if let Some(node) = message_input.get() {
+                                mention_prefix.set(pending_mention(&node.inner_text()).map(String::from));
+                            }
                             if !ev.shift_key() && ev.key() == "Enter" && !message_input.get().expect("").inner_text().trim().is_empty() {
+                                typing_notifier.stop(&ws_ctx);
                                 let path = path.get();
                                 let room_uuid = path
                                     .strip_prefix("/channel/")
@@ -107,24 +348,66 @@ pub fn Channel() -> impl IntoView {
                                     .inner_text()
                                     .trim()
                                     .to_string();
-                                let ws_payload = WsPayload::new(1, room_uuid.clone());
-                                send(serde_json::to_string(&ws_payload).unwrap().as_str());
-                                publish_msg.dispatch(PublishMsg { text, room_uuid });
+                                ws_ctx.send_frame(&ClientFrame::PostMessage { room_uuid, text, parent: None });
                                 message_input.get().expect("input element doesn't exist").set_inner_text("");
+                                mention_prefix.set(None);
                             }
                         };
+                        let current_room = room.room_uuid.clone();
                         create_effect(move |_| {
-                            if let Some(bytes) = message_bytes.get() {
-                                let msg = serde_json::from_slice::<WsPayload>(&bytes).unwrap();
-                                match msg.op_code {
-                                    11 => {
-                                            let room_uuid = path.get().strip_prefix("/channel/").expect("Provide valid uuid!").to_string();
-                                            if msg.message == room_uuid {
-                                                msg_resource.refetch();
+                            match frame.get() {
+                                // --- replies live in the thread panel, here they only bump the reply count
+                                Some(ServerFrame::NewMessage { msg }) if msg.channel == current_room => {
+                                    if let Some(sender) = &msg.msg_sender {
+                                        typing_users.update(|users| users.retain(|user| user.uuid != sender.uuid));
+                                    }
+                                    messages.update(|vec| match &msg.parent {
+                                        Some(parent_uuid) => {
+                                            if let Some(parent) = vec.iter_mut().find(|known| &known.msg_uuid == parent_uuid) {
+                                                parent.reply_count += 1;
                                             }
-                                        },
-                                     n => logging::log!("not yet registered op_code: {}", n)
+                                        }
+                                        None => {
+                                            if !vec.iter().any(|known| known.msg_uuid == msg.msg_uuid) {
+                                                vec.push(msg);
+                                            }
+                                        }
+                                    });
                                 }
+                                Some(ServerFrame::MessageEdited { msg } | ServerFrame::MessageDeleted { msg }) if msg.channel == current_room => {
+                                    messages.update(|vec| {
+                                        if let Some(known) = vec.iter_mut().find(|known| known.msg_uuid == msg.msg_uuid) {
+                                            known.apply_change(msg);
+                                        }
+                                    });
+                                }
+                                Some(ServerFrame::ReactionsChanged { room_uuid, msg_uuid, reactions }) if room_uuid == current_room => {
+                                    messages.update(|vec| {
+                                        if let Some(known) = vec.iter_mut().find(|known| known.msg_uuid == msg_uuid) {
+                                            known.reactions = reactions;
+                                        }
+                                    });
+                                }
+                                Some(ServerFrame::ReadReceipt { room_uuid, receipt }) if room_uuid == current_room && read_receipts.get_untracked() => {
+                                    receipts.update(|receipts| {
+                                        receipts.insert(receipt.user.uuid.clone(), receipt);
+                                    });
+                                }
+                                Some(ServerFrame::ReadReceiptsChanged { room_uuid, enabled }) if room_uuid == current_room => {
+                                    if !enabled {
+                                        receipts.set(HashMap::new());
+                                    }
+                                    read_receipts.set(enabled);
+                                }
+                                Some(ServerFrame::Typing { room_uuid, user, active }) if room_uuid == current_room => {
+                                    typing_users.update(|users| {
+                                        users.retain(|known| known.uuid != user.uuid);
+                                        if active {
+                                            users.push(user);
+                                        }
+                                    });
+                                }
+                                _ => {}
                             }
                         });
                         let handle_focusin = move |_: ev::FocusEvent| {
@@ -132,64 +415,87 @@ pub fn Channel() -> impl IntoView {
                                 node.set_inner_text("");
                             }
                         };
+                        let focusout_ctx = expect_context::<WebsocketCtx>();
                         let handle_focusout = move |_: ev::FocusEvent| {
+                            typing_notifier.stop(&focusout_ctx);
+                            mention_prefix.set(None);
                             if let Some(node) = message_input.get() {
                                 node.set_inner_text("Type your message...");
                             }
                         };
-                        let _root = create_node_ref::<html::Ol>();
 
+                        let members_room = room.room_uuid.clone();
+                        let viewer_uuid = user_resource.get_untracked().and_then(Result::ok).map(|user| user.uuid).unwrap_or_default();
+                        let channel_name = room.display_name(&viewer_uuid);
+                        // --- what the current user may do here, admins can be appointed while the room is open
+                        let my_role = create_rw_signal(room.role_of(&viewer_uuid).unwrap_or_default());
+                        let role = Signal::from(my_role);
+                        let thread_ctx = expect_context::<ThreadCtx>();
+                        let role_room = room.room_uuid.clone();
+                        create_effect(move |_| {
+                            if let Some(ServerFrame::RoleChanged { room_uuid, user_uuid, role }) = frame.get() {
+                                if room_uuid == role_room && user_uuid == viewer_uuid {
+                                    my_role.set(role);
+                                    thread_ctx.role.set(role);
+                                }
+                            }
+                        });
+                        // --- renames, topics and archiving show up live
+                        let settings = create_rw_signal(room.settings());
+                        let settings_room = room.room_uuid.clone();
+                        create_effect(move |_| {
+                            if let Some(ServerFrame::RoomUpdated { room_uuid, settings: updated }) = frame.get() {
+                                if room_uuid == settings_room {
+                                    settings.set(updated);
+                                }
+                            }
+                        });
+                        let channel_name = match room.kind {
+                            RoomKind::Channel => Signal::derive(move || settings.with(|settings| settings.room_name.clone())),
+                            _ => Signal::derive(move || channel_name.clone()),
+                        };
+                        let archived = move || settings.with(|settings| settings.archived);
                         view! {
-                            <div
-                                class="h-full w-full bg-transparent flex pt flex-col overflow-y-hidden"
-                                id="chat-interface"
-                            >
-                                <ChannelHeader channel_name=room.room_name/>
-                                <ol
-                                    class="flex flex-col-reverse h-[44rem] w-full bg-transparent px-4 overflow-y-scroll"
-                                    id="chat-log"
-                                    _ref=_root
+                            <div class="h-full w-full bg-transparent flex flex-row">
+                                <div
+                                    class="h-full grow bg-transparent flex pt flex-col overflow-y-hidden"
+                                    id="chat-interface"
                                 >
-                                    <For
-                                        {move || msg_resource.track()}
-                                        each=move || {
-                                            msg_resource
-                                                .get()
-                                                .unwrap_or_else(|| Ok(Vec::new()))
-                                                .unwrap_or_default()
-                                                .into_iter()
-                                                .enumerate()
-                                        }
-                                        key=|(_, msg_response)| (msg_response.created_at, msg_response.msg_uuid.clone())
-                                        children=move |(idx, _)| {
-                                            let msg = create_memo(move |_| {
-                                                msg_resource
-                                                    .and_then(|vec| {
-                                                        let len = vec.len() - 1;
-                                                        vec.get(len-idx).unwrap().clone()
-                                                    })
-                                                    .unwrap_or(Ok(MsgResponse::default()))
-                                                    .unwrap_or_default()
-                                            });
-                                            view! { <MessageBubble msg user_resource/> }
-                                        }
-                                    />
-                                </ol>
-                                <form
-                                    class="px-4 h-32 flex flex-row items-center"
-                                >
-                                    <div
-                                        on:keyup=handle_keyup
-                                        on:focusin=handle_focusin
-                                        on:focusout=handle_focusout
-                                        id="input"
-                                        role="textbox"
-                                        aria-multiline="true"
-                                        contenteditable="true"
-                                        _ref=message_input
-                                        class="grow rounded-md min-h-12 max-h-[120px] h-fit overflow-y-scroll text-white font-sans mb-2 px-2 py-3 bg-white/20 hover:bg-white/10 focus:bg-white/10 focus:outline-none border-0 w-auto text-base flex items-center"
-                                    >"Type your message..."</div>
-                                </form>
+                                    {match room.kind {
+                                        RoomKind::Group => view! {
+                                            <GroupHeader room_uuid=room.room_uuid.clone() participants=room.participants() read_receipts/>
+                                        }.into_view(),
+                                        kind => view! {
+                                            <ChannelHeader channel_name kind room_uuid=room.room_uuid.clone() role read_receipts settings/>
+                                        }.into_view(),
+                                    }}
+                                    <MessageList messages has_more loading load_older user_resource role first_unread seen_by/>
+                                    <TypingIndicator typing_users/>
+                                    <MentionPicker suggestions=mention_suggestions on_pick=pick_mention/>
+                                    <Show when=archived>
+                                        <p class="px-4 h-32 flex items-center justify-center font-sans text-white/[.65] text-sm">
+                                            "This room is archived, nobody can post here anymore"
+                                        </p>
+                                    </Show>
+                                    // kept mounted while archived, the composer handlers hold on to its node
+                                    <form
+                                        class=move || if archived() { "hidden" } else { "px-4 h-32 flex flex-row items-center" }
+                                    >
+                                        <div
+                                            on:keydown=handle_keydown
+                                            on:keyup=handle_keyup
+                                            on:focusin=handle_focusin
+                                            on:focusout=handle_focusout
+                                            id="input"
+                                            role="textbox"
+                                            aria-multiline="true"
+                                            contenteditable="true"
+                                            _ref=message_input
+                                            class="grow rounded-md min-h-12 max-h-[120px] h-fit overflow-y-scroll text-white font-sans mb-2 px-2 py-3 bg-white/20 hover:bg-white/10 focus:bg-white/10 focus:outline-none border-0 w-auto text-base flex items-center"
+                                        >"Type your message..."</div>
+                                    </form>
+                                </div>
+                                <MembersPanel room_uuid=members_room/>
                             </div>
                         }
                             .into_view()
@@ -209,58 +515,3 @@ pub fn Channel() -> impl IntoView {
         </Transition>
     }
 }
-
-#[component]
-fn MessageBubble(
-    msg: Memo<MsgResponse>,
-    user_resource: Resource<(), Result<User, ServerFnError>>,
-) -> impl IntoView {
-    let sender = move || {
-        msg.get().msg_sender.unwrap_or_default().user_name
-            == user_resource
-                .map(|user| user.clone().unwrap_or_default().user_name)
-                .unwrap_or_default()
-    };
-    let receiver_class = "bg-transparent flex flex-row mt-2";
-    let sender_class = "bg-transparent flex flex-row-reverse mt-2";
-
-    view! {
-        <li class=move || if sender() { sender_class } else { receiver_class }>
-            <div class="flex flex-shrink-0 justify-center items-center pb-1 size-9 bg-sky-500 rounded-full text-white hover:text-black hover:bg-green-300 uppercase font-sans text-2xl text-center">
-                {move || msg.get().msg_sender.unwrap_or_default().avatar.get_view()}
-            </div>
-            <div class=move || if sender() { "flex flex-col mr-2 rounded-l-lg rounded-br-lg bg-green-300 px-2 max-w-[500px]" } else { "flex flex-col ml-2 rounded-r-lg rounded-bl-lg bg-slate-300 px-2 max-w-[500px]" }>
-                <div class=move || if sender() { "flex flex-row flex-wrap justify-end" } else { "flex flex-row content-start" }>
-                    {move || if sender() {
-                        view! {
-                            <p>
-                                <span class="font-sans text-black/[.65] text-xs mr-2">
-                                    {move || msg.get().created_at.with_timezone(&Local).format("%d/%m/%Y %H:%M").to_string()}
-                                </span>
-                                <span class="font-sans text-indigo-500 text-lg">
-                                    {move || msg.get().msg_sender.unwrap_or_default().user_name}
-                                </span>
-                            </p>
-                        }
-                    } else {
-                        view! {
-                            <p>
-                                <span class="font-sans text-indigo-500 text-lg">
-                                    {move || msg.get().msg_sender.unwrap_or_default().user_name}
-                                </span>
-                                <span class="font-sans text-black/[.65] text-xs ml-2">
-                                    {move || msg.get().created_at.with_timezone(&Local).format("%d/%m/%Y %H:%M").to_string()}
-                                </span>
-                            </p>
-                        }
-                    }}
-                </div>
-                <div class=move || if sender() { "flex flex-row flex-wrap justify-end" } else { "flex flex-row flex-wrap justify-start" }>
-                    <pre class="py-1 font-sans text-black text text-wrap">
-                        {move || msg.get().message}
-                    </pre>
-                </div>
-            </div>
-        </li>
-    }
-}
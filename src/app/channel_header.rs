@@ -1,10 +1,152 @@
-use leptos::*;
+use {
+    super::{invite_panel::InvitePanel, room_settings::RoomSettingsPanel},
+    crate::{
+        models::{
+            role_model::{Permission, Role},
+            room_settings_model::RoomSettings,
+        },
+        state::rooms_manager::RoomKind,
+    },
+    leptos::*,
+};
+
+#[server(SetReadReceipts)]
+pub async fn set_read_receipts(room_uuid: String, enabled: bool) -> Result<(), ServerFnError> {
+    use crate::{
+        error::ServerError,
+        models::{frame_model::ServerFrame, role_model::Permission},
+        state::{auth, repository, rooms_manager},
+    };
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    rooms_manager
+        .modify_room(&room_uuid, &repository, |room_data| {
+            // conversations have no moderators, any participant decides for the room
+            if room_data.kind.is_conversation() {
+                if !room_data.users.contains_key(&user.uuid) {
+                    return Err(ServerError::NotAMember);
+                }
+            } else {
+                room_data.require(&user.uuid, Permission::EditSettings)?;
+            }
+            room_data.read_receipts = enabled;
+            Ok(true)
+        })
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    rooms_manager.broadcast(
+        &room_uuid,
+        ServerFrame::ReadReceiptsChanged {
+            room_uuid: room_uuid.clone(),
+            enabled,
+        },
+    );
+    Ok(())
+}
+
+#[component]
+pub fn ReadReceiptsToggle(room_uuid: String, read_receipts: RwSignal<bool>) -> impl IntoView {
+    let set_read_receipts_action = create_server_action::<SetReadReceipts>();
+    let toggle_read_receipts = move |_: ev::MouseEvent| {
+        set_read_receipts_action.dispatch(SetReadReceipts {
+            room_uuid: room_uuid.clone(),
+            enabled: !read_receipts.get_untracked(),
+        });
+    };
+
+    view! {
+        <button
+            on:click=toggle_read_receipts
+            class="font-sans text-white/[.65] text-xs hover:text-white border-none bg-transparent"
+        >
+            {move || if read_receipts.get() { "Read receipts: on" } else { "Read receipts: off" }}
+        </button>
+    }
+}
+
+// the panels hanging off the header, one open at a time
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HeaderPanel {
+    Invites,
+    Settings,
+}
 
 #[component]
-pub fn ChannelHeader(channel_name: String) -> impl IntoView {
+pub fn ChannelHeader(
+    channel_name: Signal<String>,
+    kind: RoomKind,
+    room_uuid: String,
+    role: Signal<Role>,
+    read_receipts: RwSignal<bool>,
+    settings: RwSignal<RoomSettings>,
+) -> impl IntoView {
+    let can_edit_settings = move || role.get().can(Permission::EditSettings);
+    // conversations take people in through their participants, not invites
+    let can_invite = move || kind == RoomKind::Channel && role.get().can(Permission::Invite);
+    let can_open_settings = move || kind == RoomKind::Channel && can_edit_settings();
+    let can_set_read_receipts = move || kind.is_conversation() || can_edit_settings();
+    let open_panel = create_rw_signal(None::<HeaderPanel>);
+    let toggle_panel = move |panel: HeaderPanel| {
+        open_panel.update(|open| {
+            *open = if *open == Some(panel) {
+                None
+            } else {
+                Some(panel)
+            };
+        });
+    };
+    let invite_room = room_uuid.clone();
+    let settings_room = room_uuid.clone();
+    let topic = move || settings.with(|settings| settings.topic.clone());
+    let archived = move || settings.with(|settings| settings.archived);
+
     view! {
-        <div class="select-none shrink-0 bg-slate-800/[.65] w-auto h-[50px] flex flex-row pl-2 items-center rounded-tr-xl">
-            <p class="font-sans tracking-wider text-white">"Current channel: "{channel_name}</p>
+        <div class="relative select-none shrink-0 bg-slate-800/[.65] w-auto h-[50px] flex flex-row gap-3 px-2 items-center justify-between">
+            <div class="flex flex-row items-baseline gap-3 min-w-0">
+                <p class="shrink-0 font-sans tracking-wider text-white">
+                    {match kind {
+                        RoomKind::Channel => "Current channel: ",
+                        RoomKind::Direct | RoomKind::Group => "Direct message with ",
+                    }}
+                    {channel_name}
+                </p>
+                <Show when=archived>
+                    <span class="shrink-0 rounded-full px-2 font-sans text-[11px] text-slate-900 bg-amber-300">"archived"</span>
+                </Show>
+                <p class="font-sans text-white/[.65] text-xs truncate" title=topic>{topic}</p>
+            </div>
+            <div class="shrink-0 flex flex-row items-center gap-3">
+                <Show when=move || can_invite() && !archived()>
+                    <button
+                        on:click=move |_| toggle_panel(HeaderPanel::Invites)
+                        class="font-sans text-white/[.65] text-xs hover:text-white border-none bg-transparent"
+                    >
+                        "Invite"
+                    </button>
+                </Show>
+                <Show when=can_set_read_receipts>
+                    <ReadReceiptsToggle room_uuid=room_uuid.clone() read_receipts/>
+                </Show>
+                <Show when=can_open_settings>
+                    <button
+                        on:click=move |_| toggle_panel(HeaderPanel::Settings)
+                        class="font-sans text-white/[.65] text-xs hover:text-white border-none bg-transparent"
+                    >
+                        "Settings"
+                    </button>
+                </Show>
+            </div>
+            <Show when=move || open_panel.get() == Some(HeaderPanel::Invites) && can_invite() && !archived()>
+                <InvitePanel room_uuid=invite_room.clone()/>
+            </Show>
+            <Show when=move || open_panel.get() == Some(HeaderPanel::Settings) && can_open_settings()>
+                <RoomSettingsPanel room_uuid=settings_room.clone() settings role/>
+            </Show>
         </div>
     }
 }
@@ -1,25 +1,47 @@
 use super::{
     create_or_join::{CreateNewRoom, CreateOrJoinRoomButton, JoinRoom, PopUpRoomForm},
     current_user::{get_avatar_and_name, CurrentUser, UserMenu},
-    joined_channels::{fetch_joined_channels, JoinedChannels},
+    direct_messages::DirectMessages,
+    joined_channels::{fetch_joined_channels, track_unread, JoinedChannels},
     logout::LogoutAction,
+    mention_toasts::MentionToasts,
+    thread_panel::{ThreadCtx, ThreadPanel},
+    AppPath,
+};
+use crate::models::{
+    frame_model::{ClientFrame, ServerFrame, PROTOCOL_VERSION},
+    presence_model::{HEARTBEAT_INTERVAL, IDLE_AFTER},
 };
 use leptos::*;
-use leptos_router::Outlet;
-use leptos_use::{use_websocket, UseWebsocketReturn};
+use leptos_router::{Outlet, A};
+use leptos_use::{
+    core::ConnectionReadyState, use_idle, use_interval_fn, use_websocket, UseIdleReturn,
+    UseWebsocketReturn,
+};
 use std::rc::Rc;
 
 #[derive(Clone)]
 pub struct WebsocketCtx {
     pub send: Rc<dyn Fn(&str)>,
-    pub message_bytes: Signal<Option<Vec<u8>>>,
+    pub frame: Signal<Option<ServerFrame>>,
 }
 
 impl WebsocketCtx {
     fn new(send: Rc<dyn Fn(&str)>, message_bytes: Signal<Option<Vec<u8>>>) -> Self {
-        Self {
-            send,
-            message_bytes,
+        let frame = Signal::derive(move || {
+            message_bytes.get().and_then(|bytes| {
+                serde_json::from_slice::<ServerFrame>(&bytes)
+                    .map_err(|err| logging::warn!("unknown frame from server: {}", err))
+                    .ok()
+            })
+        });
+        Self { send, frame }
+    }
+
+    pub fn send_frame(&self, frame: &ClientFrame) {
+        match serde_json::to_string(frame) {
+            Ok(text) => (self.send)(&text),
+            Err(err) => logging::error!("unable to encode frame: {}", err),
         }
     }
 }
@@ -41,13 +63,84 @@ pub fn ChatPage(logout_action: LogoutAction) -> impl IntoView {
     );
     let user_resource = create_resource(|| (), |_| get_avatar_and_name());
     let UseWebsocketReturn {
+        ready_state,
         send,
         message_bytes,
         ..
     } = use_websocket("ws://localhost:4321/ws");
+    let ws_ctx = WebsocketCtx::new(Rc::new(send), message_bytes);
+    {
+        // --- every (re)connection starts with the handshake
+        let ws_ctx = ws_ctx.clone();
+        create_effect(move |_| {
+            if ready_state.get() == ConnectionReadyState::Open {
+                ws_ctx.send_frame(&ClientFrame::Hello {
+                    version: PROTOCOL_VERSION,
+                });
+            }
+        });
+    }
+    {
+        // --- heartbeats keep the connection alive and tell the server whether the user is around
+        let UseIdleReturn { idle, .. } = use_idle(IDLE_AFTER * 1000);
+        let ws_ctx = ws_ctx.clone();
+        let send_heartbeat = move || {
+            if ready_state.get_untracked() == ConnectionReadyState::Open {
+                ws_ctx.send_frame(&ClientFrame::Heartbeat {
+                    idle: idle.get_untracked(),
+                });
+            }
+        };
+        let _ = use_interval_fn(send_heartbeat.clone(), HEARTBEAT_INTERVAL * 1000);
+        create_effect(move |_| {
+            idle.track();
+            send_heartbeat();
+        });
+    }
+    {
+        let frame = ws_ctx.frame;
+        create_effect(move |_| {
+            if let Some(ServerFrame::Error { code, message }) = frame.get() {
+                logging::warn!("websocket error {:?}: {}", code, message);
+            }
+        });
+    }
     provide_context(logout_action);
     provide_context(user_resource);
-    provide_context(WebsocketCtx::new(Rc::new(send), message_bytes));
+    provide_context(ws_ctx);
+    provide_context(ThreadCtx::new());
+    provide_context(channels_resource);
+    let unread_counts = track_unread(channels_resource);
+    {
+        // --- kicked, banned or the room deleted, the room leaves the sidebar and the user leaves the room
+        let frame = expect_context::<WebsocketCtx>().frame;
+        let path = leptos_router::use_location().pathname;
+        let navigate = leptos_router::use_navigate();
+        create_effect(move |_| {
+            let room_uuid = match frame.get() {
+                Some(ServerFrame::MemberRemoved {
+                    room_uuid,
+                    user_uuid,
+                    ..
+                }) => {
+                    let Some(Ok(user)) = user_resource.get_untracked() else {
+                        return;
+                    };
+                    if user_uuid != user.uuid {
+                        return;
+                    }
+                    channels_resource.refetch();
+                    room_uuid
+                }
+                // the sidebar refetches on its own for this one
+                Some(ServerFrame::RoomDeleted { room_uuid }) => room_uuid,
+                _ => return,
+            };
+            if path.get_untracked() == format!("/channel/{}", room_uuid) {
+                navigate(&format!("/{}", AppPath::Channel(None)), Default::default());
+            }
+        });
+    }
 
     view! {
         <div class="block absolute m-auto left-0 right-0 top-0 bottom-0 w-[91.6667%] h-[91.6667%] max-h-[91.6667%] max-w-[91.6667%] flex flex-row bg-slate-800/[.65] rounded-xl">
@@ -71,8 +164,14 @@ pub fn ChatPage(logout_action: LogoutAction) -> impl IntoView {
                         class="flex flex-col items-center h-full w-[70px] bg-slate-950/[.65] rounded-bl-xl pb-2"
                     >
                         <div id="channel-list" class="flex flex-col grow bg-transparent">
-                            <JoinedChannels channels_resource/>
+                            <JoinedChannels channels_resource unread_counts/>
                         </div>
+                        <A
+                            href=format!("/{}", AppPath::Directory)
+                            class="mb-2 h-12 w-12 flex items-center justify-center bg-sky-500 hover:bg-green-300 hover:text-black rounded-xl text-white font-sans text-2xl"
+                        >
+                            <span title="Browse public rooms">"#"</span>
+                        </A>
                         <CreateOrJoinRoomButton display_room_form set_display_room_form/>
                         <PopUpRoomForm display_room_form create_room_action join_room_action/>
                     </div>
@@ -80,14 +179,18 @@ pub fn ChatPage(logout_action: LogoutAction) -> impl IntoView {
                         id="sub-channel-navigation"
                         class="h-full w-[300px] bg-transparent rounded-l-xl flex flex-col"
                     >
+                        <DirectMessages channels_resource unread_counts/>
                         <div
                             id="sub-channels"
-                            class="grow w-full bg-slate-800/[.65] rounded-bl-xl"
-                        ></div>
+                            class="grow w-full bg-slate-800/[.65] rounded-bl-xl overflow-hidden"
+                        >
+                            <ThreadPanel/>
+                        </div>
                     </div>
                 </div>
             </div>
             <Outlet/>
+            <MentionToasts/>
         </div>
     }
 }
@@ -1,32 +1,42 @@
-use leptos::*;
+use {
+    crate::models::room_settings_model::{DESCRIPTION_MAX, ROOM_NAME_MAX},
+    leptos::*,
+};
 // use leptos_router::ActionForm;
 
 #[server(CreateNewRoom)]
-pub async fn create_new_room(room_name: String) -> Result<(), ServerFnError> {
+pub async fn create_new_room(
+    room_name: String,
+    description: String,
+    public: bool,
+) -> Result<(), ServerFnError> {
     use super::AppPath;
-    use crate::{
-        models::user_model::UserData,
-        state::{auth, pool, rooms_manager},
-    };
+    use crate::state::{auth, repository, rooms_manager};
     use chrono::Utc;
 
     let auth = auth()?;
-    let pool = pool()?;
+    let repository = repository()?;
     let rooms_manager = rooms_manager()?;
     let user = auth
         .current_user
         .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
-    let user_data = UserData::get_from_uuid(&user.uuid, &pool)
-        .await
-        .ok_or_else(|| ServerFnError::new("User does not exist"))?;
+    let user_uuid = user.uuid.clone();
     let created_at = Utc::now();
     match rooms_manager
-        .new_room(room_name.clone(), user, &pool, created_at)
+        .new_room(
+            room_name,
+            description,
+            public,
+            user,
+            &repository,
+            created_at,
+        )
         .await
     {
         Ok(room_uuid) => {
-            user_data
-                .add_channel(room_uuid.clone(), &pool)
+            repository
+                .users
+                .add_channel(&user_uuid, &room_uuid)
                 .await
                 .map_err(|err| ServerFnError::new(err))?;
             Ok(leptos_axum::redirect(
@@ -37,29 +47,41 @@ pub async fn create_new_room(room_name: String) -> Result<(), ServerFnError> {
     }
 }
 
+// `code` is an invite code or a whole invite link, room uuids don't let anyone in
 #[server(JoinRoom)]
-pub async fn join_room(room_uuid: String) -> Result<(), ServerFnError> {
+pub async fn join_room(code: String) -> Result<(), ServerFnError> {
     use super::AppPath;
     use crate::{
-        models::user_model::UserData,
-        state::{auth, pool, rooms_manager},
+        models::invite_model::Invite,
+        state::{auth, repository, rooms_manager},
     };
+    use chrono::Utc;
 
     let auth = auth()?;
-    let pool = pool()?;
+    let repository = repository()?;
     let rooms_manager = rooms_manager()?;
     let user = auth
         .current_user
         .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
-    let user_data = UserData::get_from_uuid(&user.uuid, &pool)
+    let user_uuid = user.uuid.clone();
+    match rooms_manager
+        .join_with_invite(Invite::code_from(&code), user, &repository, Utc::now())
         .await
-        .ok_or_else(|| ServerFnError::new("User does not exist"))?;
-    match rooms_manager.join_room(&room_uuid, user, &pool).await {
-        Ok(_) => {
-            user_data
-                .add_channel(room_uuid.clone(), &pool)
-                .await
-                .map_err(|err| ServerFnError::new(err))?;
+    {
+        Ok(room_uuid) => {
+            let joined_channels = repository
+                .users
+                .get_user_by_uuid(&user_uuid)
+                .await?
+                .map(|user_data| user_data.joined_channels)
+                .unwrap_or_default();
+            if !joined_channels.contains(&room_uuid) {
+                repository
+                    .users
+                    .add_channel(&user_uuid, &room_uuid)
+                    .await
+                    .map_err(|err| ServerFnError::new(err))?;
+            }
             Ok(leptos_axum::redirect(
                 &AppPath::Channel(Some(room_uuid)).to_string(),
             ))
@@ -97,23 +119,41 @@ pub fn PopUpRoomForm(
         <Show when=move || display_room_form.get()>
             {
                 let cr_node = create_node_ref::<html::Input>();
+                let description_node = create_node_ref::<html::Input>();
+                let public_node = create_node_ref::<html::Input>();
                 let join_node = create_node_ref::<html::Input>();
                 let cnr = move |ev: ev::SubmitEvent| {
                     ev.prevent_default();
                     let room_name = cr_node.get().expect("input element does not exist").value();
-                    create_room_action.dispatch(CreateNewRoom { room_name });
+                    let description = description_node
+                        .get()
+                        .expect("input element does not exist")
+                        .value();
+                    let public = public_node
+                        .get()
+                        .expect("input element does not exist")
+                        .checked();
+                    create_room_action.dispatch(CreateNewRoom {
+                        room_name,
+                        description,
+                        public,
+                    });
                     cr_node
                         .get()
                         .expect("input element does not exist")
                         .set_value("");
+                    description_node
+                        .get()
+                        .expect("input element does not exist")
+                        .set_value("");
                 };
                 let jtr = move |ev: ev::SubmitEvent| {
                     ev.prevent_default();
-                    let room_uuid = join_node
+                    let code = join_node
                         .get()
                         .expect("input element does not exist")
                         .value();
-                    join_room_action.dispatch(JoinRoom { room_uuid });
+                    join_room_action.dispatch(JoinRoom { code });
                     join_node
                         .get()
                         .expect("input element does not exist")
@@ -121,7 +161,7 @@ pub fn PopUpRoomForm(
                 };
 
                 view! {
-                    <div class="block absolute m-auto left-0 right-0 top-0 bottom-0 flex flex-col bg-slate-800 size-[25rem] rounded-xl py-4">
+                    <div class="block absolute m-auto left-0 right-0 top-0 bottom-0 flex flex-col bg-slate-800 w-[25rem] h-[30rem] rounded-xl py-4">
                         <form
                             on:submit=cnr
                             class="flex flex-col px-4">
@@ -135,9 +175,20 @@ pub fn PopUpRoomForm(
                                 <input
                                     _ref=cr_node
                                     required
+                                    maxlength=ROOM_NAME_MAX
                                     class="text-white pl-1 bg-white/20 hover:bg-white/10 focus:bg-white/10 focus:outline-none border-0 w-auto mt-4 text-base h-10"
                                     placeholder="Enter new room name..."
                                     name="room_name"/>
+                                <input
+                                    _ref=description_node
+                                    maxlength=DESCRIPTION_MAX
+                                    class="text-white pl-1 bg-white/20 hover:bg-white/10 focus:bg-white/10 focus:outline-none border-0 w-auto mt-2 text-base h-10"
+                                    placeholder="What is it about? (optional)"
+                                    name="description"/>
+                                <label class="flex flex-row items-center gap-2 mt-2 font-sans text-white text-sm">
+                                    <input _ref=public_node type="checkbox" name="public"/>
+                                    "List in the room directory"
+                                </label>
                                 <button class="text-white hover:text-black mt-2 w-full bg-sky-500 hover:bg-green-300 rounded-lg border-0 w-fit py-1 px-1">
                                     "create"
                                 </button>
@@ -157,8 +208,8 @@ pub fn PopUpRoomForm(
                                     _ref=join_node
                                     required
                                     class="text-white pl-1 bg-white/20 hover:bg-white/10 focus:bg-white/10 focus:outline-none border-0 w-auto mt-4 text-base h-10"
-                                    placeholder="Enter room id..."
-                                    name="room_uuid"/>
+                                    placeholder="Enter invite code or link..."
+                                    name="code"/>
                                 <button class="text-white hover:text-black mt-2 w-full bg-sky-500 hover:bg-green-300 rounded-lg border-0 w-fit py-1 px-1">
                                     "join"
                                 </button>
@@ -0,0 +1,256 @@
+use {
+    super::joined_channels::{channels_of_kind, ChannelsResource, UnreadBadge},
+    crate::{
+        models::{read_marker_model::UnreadCount, user_model::User},
+        state::rooms_manager::{RoomKind, GROUP_MAX, GROUP_MIN},
+    },
+    leptos::*,
+    leptos_router::A,
+    std::collections::HashMap,
+};
+
+const SEARCH_LIMIT: usize = 8;
+
+#[server(SearchUsers, "/api", "GetJson")]
+pub async fn search_users(query: String) -> Result<Vec<User>, ServerFnError> {
+    use crate::state::{auth, repository};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    // one extra row makes up for the current user being left out
+    let vec_user = repository
+        .users
+        .search_users(query, SEARCH_LIMIT + 1)
+        .await?
+        .iter()
+        .filter(|user_data| user_data.uuid != user.uuid)
+        .map(User::from_user_data)
+        .take(SEARCH_LIMIT)
+        .collect::<Vec<User>>();
+    Ok(vec_user)
+}
+
+#[server(OpenDirectMessage)]
+pub async fn open_direct_message(user_uuid: String) -> Result<(), ServerFnError> {
+    use super::AppPath;
+    use crate::{
+        error::ServerError,
+        state::{auth, repository, rooms_manager},
+    };
+    use chrono::Utc;
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let other = repository
+        .users
+        .get_user_by_uuid(&user_uuid)
+        .await?
+        .map(|user_data| User::from_user_data(&user_data))
+        .ok_or_else(|| ServerFnError::new(ServerError::UserDoesNotExist))?;
+    let room_uuid = rooms_manager
+        .open_direct(user, other, &repository, Utc::now())
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    Ok(leptos_axum::redirect(
+        &AppPath::Channel(Some(room_uuid)).to_string(),
+    ))
+}
+
+#[server(CreateGroup)]
+pub async fn create_group(user_uuids: Vec<String>) -> Result<(), ServerFnError> {
+    use super::AppPath;
+    use crate::{
+        error::ServerError,
+        state::{auth, repository, rooms_manager},
+    };
+    use chrono::Utc;
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let mut others = Vec::new();
+    for user_uuid in user_uuids.iter() {
+        let other = repository
+            .users
+            .get_user_by_uuid(user_uuid)
+            .await?
+            .ok_or_else(|| ServerFnError::new(ServerError::UserDoesNotExist))?;
+        others.push(User::from_user_data(&other));
+    }
+    let room_uuid = rooms_manager
+        .new_group(user, others, &repository, Utc::now())
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    Ok(leptos_axum::redirect(
+        &AppPath::Channel(Some(room_uuid)).to_string(),
+    ))
+}
+
+#[component]
+pub fn DirectMessages(
+    channels_resource: ChannelsResource,
+    unread_counts: RwSignal<HashMap<String, UnreadCount>>,
+) -> impl IntoView {
+    let path = leptos_router::use_location().pathname;
+    let open_action = create_server_action::<OpenDirectMessage>();
+    let group_action = create_server_action::<CreateGroup>();
+    create_effect(move |_| {
+        if open_action.version().get() + group_action.version().get() > 0 {
+            channels_resource.refetch();
+        }
+    });
+
+    // --- picking a user to talk to, or several to start a group with
+    let (query, set_query) = create_signal(String::new());
+    let search_resource = create_resource(move || query.get(), search_users);
+    let group_mode = create_rw_signal(false);
+    let selected = create_rw_signal(Vec::<User>::new());
+    let pick_user = move |user: User| {
+        set_query.set(String::new());
+        if group_mode.get_untracked() {
+            selected.update(|vec| {
+                // the creator takes one of the seats
+                if !vec.contains(&user) && vec.len() < GROUP_MAX - 1 {
+                    vec.push(user);
+                }
+            });
+        } else {
+            open_action.dispatch(OpenDirectMessage {
+                user_uuid: user.uuid,
+            });
+        }
+    };
+    let toggle_group_mode = move |_: ev::MouseEvent| {
+        group_mode.update(|group_mode| *group_mode = !*group_mode);
+        selected.set(Vec::new());
+    };
+    let start_group = move |_: ev::MouseEvent| {
+        let user_uuids = selected
+            .get_untracked()
+            .into_iter()
+            .map(|user| user.uuid)
+            .collect::<Vec<String>>();
+        group_action.dispatch(CreateGroup { user_uuids });
+        group_mode.set(false);
+        selected.set(Vec::new());
+    };
+
+    view! {
+        <div class="shrink-0 flex flex-col max-h-[45%] px-2 pt-2 border-b border-white/[.15]">
+            <div class="flex flex-row items-center justify-between px-1 pb-1">
+                <p class="font-sans tracking-wider text-white text-sm">"Direct messages"</p>
+                <button
+                    on:click=toggle_group_mode
+                    class="font-sans text-white/[.65] text-xs hover:text-white border-none bg-transparent"
+                >
+                    {move || if group_mode.get() { "Cancel" } else { "New group" }}
+                </button>
+            </div>
+            <Show when=move || group_mode.get()>
+                <div class="flex flex-row flex-wrap gap-1 pb-1">
+                    <For
+                        each=move || selected.get()
+                        key=|user| user.uuid.clone()
+                        children=move |user| {
+                            let removed = user.clone();
+                            view! {
+                                <button
+                                    on:click=move |_| selected.update(|vec| vec.retain(|known| known != &removed))
+                                    class="rounded-full px-2 font-sans text-xs text-white bg-sky-500 hover:bg-sky-400 border-none"
+                                >
+                                    {user.user_name}" ✕"
+                                </button>
+                            }
+                        }
+                    />
+                    <button
+                        on:click=start_group
+                        disabled=move || selected.with(Vec::len) + 1 < GROUP_MIN
+                        class="rounded-full px-2 font-sans text-xs text-slate-900 bg-green-300 disabled:opacity-40 border-none"
+                    >
+                        "Start group"
+                    </button>
+                </div>
+            </Show>
+            <input
+                on:input=move |ev| set_query.set(event_target_value(&ev))
+                prop:value=query
+                placeholder=move || if group_mode.get() { "Add people..." } else { "Find a user..." }
+                class="rounded-md px-2 py-1 text-white font-sans text-sm bg-white/20 hover:bg-white/10 focus:bg-white/10 focus:outline-none border-0"
+            />
+            <Transition fallback=|| ()>
+                <ul class="flex flex-col">
+                    {move || {
+                        search_resource
+                            .get()
+                            .and_then(Result::ok)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|user| {
+                                let picked = user.clone();
+                                view! {
+                                    <li
+                                        on:click=move |_| pick_user(picked.clone())
+                                        class="px-2 py-1 cursor-pointer rounded font-sans text-sm text-white hover:bg-white/10"
+                                    >
+                                        {user.user_name}
+                                    </li>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </ul>
+            </Transition>
+            <ul class="flex flex-col py-2 overflow-y-scroll">
+                <For
+                    each=move || channels_of_kind(channels_resource, &[RoomKind::Direct, RoomKind::Group])
+                    key=|direct| (direct.uuid.clone(), direct.name.clone())
+                    children=move |direct| {
+                        let href = format!("/channel/{}", direct.uuid);
+                        let active_href = href.clone();
+                        let count_uuid = direct.uuid.clone();
+                        let unread = Signal::derive(move || {
+                            unread_counts.with(|counts| counts.get(&count_uuid).copied().unwrap_or_default())
+                        });
+                        let initial = match direct.kind {
+                            RoomKind::Group => "#".to_string(),
+                            _ => direct.name.chars().next().unwrap_or_default().to_string(),
+                        };
+                        view! {
+                            <li>
+                                <A
+                                    href
+                                    class=move || if path.get() == active_href {
+                                        "flex flex-row items-center gap-2 px-1 py-1 rounded bg-white/20"
+                                    } else {
+                                        "flex flex-row items-center gap-2 px-1 py-1 rounded hover:bg-white/10"
+                                    }
+                                >
+                                    <span class="relative shrink-0 size-7 rounded-full bg-sky-500 text-white font-sans text-sm uppercase text-center leading-7">
+                                        {initial}
+                                        <UnreadBadge unread/>
+                                    </span>
+                                    <span class="font-sans text-white text-sm truncate">{direct.name}</span>
+                                </A>
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+        </div>
+    }
+}
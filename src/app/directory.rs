@@ -0,0 +1,232 @@
+use {
+    super::joined_channels::ChannelsResource,
+    crate::models::directory_model::PublicRoom,
+    chrono::{DateTime, Local, Utc},
+    leptos::*,
+    leptos_router::A,
+    leptos_use::signal_debounced,
+};
+
+const DIRECTORY_LIMIT: usize = 50;
+// how long the search box has to sit still before the server is asked
+const SEARCH_DEBOUNCE_MS: f64 = 300.0;
+
+// one page of the directory, sorted by name, `start` is the number of rooms already shown
+#[server(SearchDirectory, "/api", "GetJson")]
+pub async fn search_directory(
+    query: String,
+    start: usize,
+) -> Result<Vec<PublicRoom>, ServerFnError> {
+    use crate::state::{auth, repository};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let vec_room = repository
+        .rooms
+        .search_public_rooms(query.trim(), start, DIRECTORY_LIMIT)
+        .await?;
+    let room_uuids = vec_room
+        .iter()
+        .map(|room_data| room_data.room_uuid.clone())
+        .collect::<Vec<String>>();
+    let last_activity = repository.messages.last_activity(&room_uuids).await?;
+    Ok(vec_room
+        .into_iter()
+        .map(|room_data| PublicRoom {
+            last_activity: last_activity
+                .get(&room_data.room_uuid)
+                .copied()
+                .unwrap_or(room_data.created_at),
+            member_count: room_data.users.len(),
+            joined: room_data.users.contains_key(&user.uuid),
+            room_uuid: room_data.room_uuid,
+            room_name: room_data.room_name,
+            description: room_data.description,
+        })
+        .collect())
+}
+
+#[server(JoinPublicRoom)]
+pub async fn join_public_room(room_uuid: String) -> Result<(), ServerFnError> {
+    use super::AppPath;
+    use crate::state::{auth, repository, rooms_manager};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let user_uuid = user.uuid.clone();
+    rooms_manager
+        .join_public(&room_uuid, user, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    repository
+        .users
+        .add_channel(&user_uuid, &room_uuid)
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    Ok(leptos_axum::redirect(
+        &AppPath::Channel(Some(room_uuid)).to_string(),
+    ))
+}
+
+fn active_label(last_activity: DateTime<Utc>) -> String {
+    let elapsed = Utc::now() - last_activity;
+    if elapsed.num_minutes() < 1 {
+        "active just now".to_string()
+    } else if elapsed.num_hours() < 1 {
+        format!("active {}m ago", elapsed.num_minutes())
+    } else if elapsed.num_days() < 1 {
+        format!("active {}h ago", elapsed.num_hours())
+    } else if elapsed.num_days() < 30 {
+        format!("active {}d ago", elapsed.num_days())
+    } else {
+        format!(
+            "active {}",
+            last_activity.with_timezone(&Local).format("%d/%m/%Y")
+        )
+    }
+}
+
+// every public channel, for finding rooms nobody shared with you
+#[component]
+pub fn Directory() -> impl IntoView {
+    let channels_resource = expect_context::<ChannelsResource>();
+    let join_action = create_server_action::<JoinPublicRoom>();
+    create_effect(move |_| {
+        if join_action.version().get() > 0 {
+            channels_resource.refetch();
+        }
+    });
+    let (query, set_query) = create_signal(String::new());
+    let search_query = signal_debounced(query, SEARCH_DEBOUNCE_MS);
+    let directory_resource = create_resource(
+        move || search_query.get(),
+        |query| search_directory(query, 0),
+    );
+    // --- pages past the first, dropped whenever the search changes
+    let more_action = create_server_action::<SearchDirectory>();
+    let more_rooms = create_rw_signal(Vec::<PublicRoom>::new());
+    let has_more = create_rw_signal(false);
+    let more_query = store_value(String::new());
+    create_effect(move |_| {
+        if let Some(Ok(first_page)) = directory_resource.get() {
+            more_rooms.set(Vec::new());
+            has_more.set(first_page.len() == DIRECTORY_LIMIT);
+        }
+    });
+    create_effect(move |_| {
+        // a page asked for before the search changed belongs to the old results
+        if let Some(Ok(page)) = more_action.value().get() {
+            if more_query.get_value() == search_query.get_untracked() {
+                has_more.set(page.len() == DIRECTORY_LIMIT);
+                more_rooms.update(|rooms| rooms.extend(page));
+            }
+        }
+    });
+    let show_more = move |_: ev::MouseEvent| {
+        let query = search_query.get_untracked();
+        let first_len = directory_resource
+            .get_untracked()
+            .and_then(Result::ok)
+            .map_or(0, |first_page| first_page.len());
+        let start = first_len + more_rooms.with_untracked(Vec::len);
+        more_query.set_value(query.clone());
+        more_action.dispatch(SearchDirectory { query, start });
+    };
+    let join_error = move || {
+        join_action
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| err.to_string())
+    };
+
+    view! {
+        <div class="h-full grow flex flex-col">
+            <div class="select-none shrink-0 bg-slate-800/[.65] w-auto h-[50px] flex flex-row px-2 items-center justify-between">
+                <p class="font-sans tracking-wider text-white">"Room directory"</p>
+                <input
+                    on:input=move |ev| set_query.set(event_target_value(&ev))
+                    prop:value=query
+                    placeholder="Search rooms..."
+                    class="w-64 rounded-md px-2 py-1 text-white font-sans text-sm bg-white/20 hover:bg-white/10 focus:bg-white/10 focus:outline-none border-0"
+                />
+            </div>
+            {move || join_error().map(|err| view! {
+                <p class="px-4 pt-2 font-sans text-red-400 text-sm">{err}</p>
+            })}
+            <Transition fallback=|| view! { <p class="p-4 font-sans text-white">"Loading..."</p> }>
+                <ul class="grow flex flex-col gap-2 p-4 overflow-y-auto">
+                    {move || {
+                        let mut vec_public = directory_resource.get().and_then(Result::ok).unwrap_or_default();
+                        vec_public.extend(more_rooms.get());
+                        if vec_public.is_empty() {
+                            return view! {
+                                <p class="font-sans text-white/[.65] text-sm text-center">"No public rooms found"</p>
+                            }
+                            .into_view();
+                        }
+                        vec_public
+                            .into_iter()
+                            .map(|public| {
+                                let room_uuid = public.room_uuid.clone();
+                                view! {
+                                    <li class="flex flex-row items-center gap-3 rounded-lg bg-white/10 px-3 py-2">
+                                        <div class="flex flex-col grow min-w-0">
+                                            <p class="font-sans text-white truncate">{public.room_name}</p>
+                                            {(!public.description.is_empty()).then(|| view! {
+                                                <p class="font-sans text-white/[.65] text-sm truncate">{public.description}</p>
+                                            })}
+                                            <p class="font-sans text-white/[.45] text-xs">
+                                                {if public.member_count == 1 { "1 member".to_string() } else { format!("{} members", public.member_count) }}
+                                                " · "
+                                                {active_label(public.last_activity)}
+                                            </p>
+                                        </div>
+                                        {if public.joined {
+                                            view! {
+                                                <A
+                                                    href=format!("/channel/{}", room_uuid)
+                                                    class="shrink-0 font-sans text-sm text-white/[.65] hover:text-white"
+                                                >
+                                                    "Open"
+                                                </A>
+                                            }
+                                            .into_view()
+                                        } else {
+                                            view! {
+                                                <button
+                                                    on:click=move |_| join_action.dispatch(JoinPublicRoom { room_uuid: room_uuid.clone() })
+                                                    disabled=move || join_action.pending().get()
+                                                    class="shrink-0 rounded-lg px-3 py-1 font-sans text-sm text-white hover:text-black bg-sky-500 hover:bg-green-300 border-0"
+                                                >
+                                                    "Join"
+                                                </button>
+                                            }
+                                            .into_view()
+                                        }}
+                                    </li>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                    <Show when=move || has_more.get()>
+                        <button
+                            on:click=show_more.clone()
+                            disabled=move || more_action.pending().get()
+                            class="self-center font-sans text-sm text-white/[.65] hover:text-white border-none bg-transparent"
+                        >
+                            "Show more"
+                        </button>
+                    </Show>
+                </ul>
+            </Transition>
+        </div>
+    }
+}
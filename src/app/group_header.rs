@@ -0,0 +1,147 @@
+use {
+    super::{
+        channel_header::ReadReceiptsToggle, chat::WebsocketCtx, direct_messages::search_users,
+    },
+    crate::{
+        models::{frame_model::ServerFrame, user_model::User},
+        state::rooms_manager::GROUP_MAX,
+    },
+    leptos::*,
+};
+
+#[server(AddGroupParticipants)]
+pub async fn add_group_participants(
+    room_uuid: String,
+    user_uuids: Vec<String>,
+) -> Result<(), ServerFnError> {
+    use crate::{
+        error::ServerError,
+        state::{auth, repository, rooms_manager},
+    };
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let mut new_users = Vec::new();
+    for user_uuid in user_uuids.iter() {
+        let new_user = repository
+            .users
+            .get_user_by_uuid(user_uuid)
+            .await?
+            .ok_or_else(|| ServerFnError::new(ServerError::UserDoesNotExist))?;
+        new_users.push(User::from_user_data(&new_user));
+    }
+    rooms_manager
+        .add_to_group(&room_uuid, &user, new_users, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))
+}
+
+// a group has no name, its title is the list of who is in it
+#[component]
+pub fn GroupHeader(
+    room_uuid: String,
+    participants: Vec<User>,
+    read_receipts: RwSignal<bool>,
+) -> impl IntoView {
+    let frame = expect_context::<WebsocketCtx>().frame;
+    let user_resource = expect_context::<Resource<(), Result<User, ServerFnError>>>();
+    let participants = create_rw_signal(participants);
+    let current_room = room_uuid.clone();
+    let receipts_room = room_uuid.clone();
+    create_effect(move |_| {
+        if let Some(ServerFrame::ParticipantsChanged { room_uuid, users }) = frame.get() {
+            if room_uuid == current_room {
+                participants.set(users);
+            }
+        }
+    });
+    let title = move || {
+        let current_uuid = user_resource
+            .get()
+            .and_then(Result::ok)
+            .map(|user| user.uuid)
+            .unwrap_or_default();
+        participants.with(|vec| {
+            vec.iter()
+                .map(|user| {
+                    if user.uuid == current_uuid {
+                        "you".to_string()
+                    } else {
+                        user.user_name.clone()
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(", ")
+        })
+    };
+
+    // --- any participant can bring more people in
+    let add_action = create_server_action::<AddGroupParticipants>();
+    let adding = create_rw_signal(false);
+    let (query, set_query) = create_signal(String::new());
+    let search_resource = create_resource(move || query.get(), search_users);
+    let add_user = move |user: User| {
+        set_query.set(String::new());
+        adding.set(false);
+        add_action.dispatch(AddGroupParticipants {
+            room_uuid: room_uuid.clone(),
+            user_uuids: vec![user.uuid],
+        });
+    };
+    let add_user = store_value(add_user);
+
+    view! {
+        <div class="relative select-none shrink-0 bg-slate-800/[.65] w-auto h-[50px] flex flex-row px-2 items-center justify-between">
+            <p class="font-sans tracking-wider text-white truncate" title=title>{title}</p>
+            <div class="shrink-0 flex flex-row items-center gap-3">
+                <ReadReceiptsToggle room_uuid=receipts_room read_receipts/>
+                <Show when=move || participants.with(Vec::len) < GROUP_MAX>
+                    <button
+                        on:click=move |_| adding.update(|adding| *adding = !*adding)
+                        class="shrink-0 font-sans text-white/[.65] text-xs hover:text-white border-none bg-transparent"
+                    >
+                        "Add people"
+                    </button>
+                </Show>
+            </div>
+            <Show when=move || adding.get()>
+                <div class="absolute right-2 top-[50px] z-10 w-64 flex flex-col rounded-md bg-slate-800 p-2 shadow-lg">
+                    <input
+                        on:input=move |ev| set_query.set(event_target_value(&ev))
+                        prop:value=query
+                        placeholder="Find a user..."
+                        class="rounded-md px-2 py-1 text-white font-sans text-sm bg-white/20 focus:outline-none border-0"
+                    />
+                    <Transition fallback=|| ()>
+                        <ul class="flex flex-col">
+                            {move || {
+                                search_resource
+                                    .get()
+                                    .and_then(Result::ok)
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .filter(|user| participants.with(|vec| !vec.contains(user)))
+                                    .map(|user| {
+                                        let picked = user.clone();
+                                        view! {
+                                            <li
+                                                on:click=move |_| add_user.with_value(|add_user| add_user(picked.clone()))
+                                                class="px-2 py-1 cursor-pointer rounded font-sans text-sm text-white hover:bg-white/10"
+                                            >
+                                                {user.user_name}
+                                            </li>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+                        </ul>
+                    </Transition>
+                </div>
+            </Show>
+        </div>
+    }
+}
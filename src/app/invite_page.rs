@@ -0,0 +1,123 @@
+use {
+    super::{create_or_join::JoinRoom, AppPath},
+    crate::models::invite_model::InvitePreview,
+    chrono::Local,
+    leptos::*,
+    leptos_router::{use_params_map, A},
+};
+
+#[server(PreviewInvite, "/api", "GetJson")]
+pub async fn preview_invite(code: String) -> Result<InvitePreview, ServerFnError> {
+    use crate::{
+        error::ServerError,
+        state::{auth, repository, rooms_manager},
+    };
+    use chrono::Utc;
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let invite = repository
+        .invites
+        .get_invite(&code)
+        .await?
+        .filter(|invite| invite.is_usable(Utc::now()))
+        .ok_or_else(|| ServerFnError::new(ServerError::InvalidInvite))?;
+    let room_data = rooms_manager
+        .validate_uuid(&invite.room_uuid, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    Ok(InvitePreview {
+        code: invite.code,
+        room_uuid: room_data.room_uuid.clone(),
+        room_name: room_data.room_name.clone(),
+        member_count: room_data.users.len(),
+        expires_at: invite.expires_at,
+        joined: room_data.users.contains_key(&user.uuid),
+    })
+}
+
+// `/invite/:code`, shows the room behind the code and joins it with one click
+#[component]
+pub fn InvitePage(is_auth: bool) -> impl IntoView {
+    let params = use_params_map();
+    let code = move || params.with(|params| params.get("code").cloned().unwrap_or_default());
+    let preview_resource = create_resource(code, preview_invite);
+    let join_action = create_server_action::<JoinRoom>();
+    let join_error = move || {
+        join_action
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| err.to_string())
+    };
+
+    view! {
+        <div class="block absolute m-auto left-0 right-0 top-0 bottom-0 flex flex-col items-center justify-center bg-slate-800/[.65] py-2.5 px-8 rounded-xl size-[27rem]">
+            {move || if !is_auth {
+                view! {
+                    <p class="font-sans text-white text-center">"Log in to see this invite"</p>
+                    <A class="mt-3 text-indigo-400" href=AppPath::Login>"Login"</A>
+                }
+                .into_view()
+            } else {
+                view! {
+                    <Transition fallback=|| view! { <p class="font-sans text-white">"Loading..."</p> }>
+                        {move || preview_resource.get().map(|preview| match preview {
+                            Ok(preview) => {
+                                let InvitePreview { code, room_uuid, room_name, member_count, expires_at, joined } = preview;
+                                view! {
+                                    <p class="font-sans text-white/[.65] text-sm">"You have been invited to"</p>
+                                    <h1 class="mt-2 font-sans text-white text-center text-2xl break-all">{room_name}</h1>
+                                    <p class="mt-2 font-sans text-white/[.65] text-sm">
+                                        {if member_count == 1 { "1 member".to_string() } else { format!("{} members", member_count) }}
+                                    </p>
+                                    {expires_at.map(|expires_at| view! {
+                                        <p class="font-sans text-white/[.65] text-xs">
+                                            "Invite expires "{expires_at.with_timezone(&Local).format("%d/%m/%Y %H:%M").to_string()}
+                                        </p>
+                                    })}
+                                    {if joined {
+                                        view! {
+                                            <A
+                                                class="mt-4 font-sans text-white bg-sky-500 hover:bg-green-300 hover:text-black rounded-lg py-1 px-4"
+                                                href=format!("/channel/{}", room_uuid)
+                                            >
+                                                "Open room"
+                                            </A>
+                                        }
+                                        .into_view()
+                                    } else {
+                                        view! {
+                                            <button
+                                                on:click=move |_| join_action.dispatch(JoinRoom { code: code.clone() })
+                                                disabled=move || join_action.pending().get()
+                                                class="mt-4 font-sans text-white hover:text-black bg-sky-500 hover:bg-green-300 rounded-lg border-0 py-1 px-4"
+                                            >
+                                                "Join room"
+                                            </button>
+                                        }
+                                        .into_view()
+                                    }}
+                                    {move || join_error().map(|err| view! {
+                                        <p class="mt-2 font-sans text-red-400 text-sm text-center">{err}</p>
+                                    })}
+                                }
+                                .into_view()
+                            }
+                            Err(_) => view! {
+                                <p class="font-sans text-white text-center">"This invite is invalid, expired or used up"</p>
+                                <A class="mt-3 text-indigo-400" href="/channel">"Back to chat"</A>
+                            }
+                            .into_view(),
+                        })}
+                    </Transition>
+                }
+                .into_view()
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,250 @@
+use {
+    super::AppPath,
+    crate::models::invite_model::Invite,
+    chrono::{Local, Utc},
+    leptos::*,
+};
+
+// choices offered when creating an invite, seconds until it expires
+const EXPIRY_OPTIONS: &[(&str, Option<i64>)] = &[
+    ("30 minutes", Some(30 * 60)),
+    ("1 hour", Some(60 * 60)),
+    ("1 day", Some(24 * 60 * 60)),
+    ("7 days", Some(7 * 24 * 60 * 60)),
+    ("never", None),
+];
+const MAX_USES_OPTIONS: &[(&str, Option<u32>)] = &[
+    ("no limit", None),
+    ("1 use", Some(1)),
+    ("5 uses", Some(5)),
+    ("10 uses", Some(10)),
+    ("25 uses", Some(25)),
+    ("100 uses", Some(100)),
+];
+
+#[server(CreateInvite)]
+pub async fn create_invite(
+    room_uuid: String,
+    expires_in: Option<i64>,
+    max_uses: Option<u32>,
+) -> Result<Invite, ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
+    use chrono::Duration;
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    // only the choices the panel offers, anything else would overflow the expiry or never run out
+    if !EXPIRY_OPTIONS
+        .iter()
+        .any(|(_, option)| *option == expires_in)
+        || !MAX_USES_OPTIONS
+            .iter()
+            .any(|(_, option)| *option == max_uses)
+    {
+        return Err(ServerFnError::new("Invite options are not valid"));
+    }
+    let created_at = Utc::now();
+    let expires_at = expires_in.map(|secs| created_at + Duration::seconds(secs));
+    rooms_manager
+        .create_invite(
+            &room_uuid,
+            &user,
+            expires_at,
+            max_uses,
+            &repository,
+            created_at,
+        )
+        .await
+        .map_err(|err| ServerFnError::new(err))
+}
+
+// moderators see every invite of the room, everyone else only their own
+#[server(ListInvites, "/api", "GetJson")]
+pub async fn list_invites(room_uuid: String) -> Result<Vec<Invite>, ServerFnError> {
+    use crate::{
+        models::role_model::Permission,
+        state::{auth, repository, rooms_manager},
+    };
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let room_data = rooms_manager
+        .member_room(&room_uuid, &user.uuid, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+    let see_all = room_data.can(&user.uuid, Permission::EditSettings);
+    Ok(repository
+        .invites
+        .get_room_invites(&room_uuid)
+        .await?
+        .into_iter()
+        .filter(|invite| see_all || invite.created_by == user.uuid)
+        .collect())
+}
+
+#[server(RevokeInvite)]
+pub async fn revoke_invite(code: String) -> Result<(), ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    rooms_manager
+        .revoke_invite(&code, &user, &repository, Utc::now())
+        .await
+        .map_err(|err| ServerFnError::new(err))
+}
+
+fn invite_status(invite: &Invite) -> String {
+    let uses = match invite.max_uses {
+        Some(max_uses) => format!("{}/{} uses", invite.uses, max_uses),
+        None => format!("{} uses", invite.uses),
+    };
+    let now = Utc::now();
+    let state = if invite.revoked_at.is_some() {
+        "revoked".to_string()
+    } else if invite
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= now)
+    {
+        "expired".to_string()
+    } else if !invite.is_usable(now) {
+        "used up".to_string()
+    } else {
+        match invite.expires_at {
+            Some(expires_at) => format!(
+                "expires {}",
+                expires_at.with_timezone(&Local).format("%d/%m/%Y %H:%M")
+            ),
+            None => "never expires".to_string(),
+        }
+    };
+    format!("{} · {}", uses, state)
+}
+
+#[component]
+pub fn InvitePanel(room_uuid: String) -> impl IntoView {
+    let create_action = create_server_action::<CreateInvite>();
+    let revoke_action = create_server_action::<RevokeInvite>();
+    let list_room = room_uuid.clone();
+    let invites_resource = create_resource(
+        move || (create_action.version().get(), revoke_action.version().get()),
+        move |_| list_invites(list_room.clone()),
+    );
+    let (expiry_idx, set_expiry_idx) = create_signal(2_usize);
+    let (max_uses_idx, set_max_uses_idx) = create_signal(0_usize);
+    let create_invite = move |_: ev::MouseEvent| {
+        create_action.dispatch(CreateInvite {
+            room_uuid: room_uuid.clone(),
+            expires_in: EXPIRY_OPTIONS[expiry_idx.get_untracked()].1,
+            max_uses: MAX_USES_OPTIONS[max_uses_idx.get_untracked()].1,
+        });
+    };
+    let create_error = move || {
+        create_action
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| err.to_string())
+    };
+    // only ever rendered in the browser, the panel starts closed
+    let invite_link = |code: &str| {
+        let origin = window().location().origin().unwrap_or_default();
+        format!("{}{}", origin, AppPath::Invite(code.to_string()))
+    };
+
+    view! {
+        <div class="absolute right-2 top-[50px] z-10 w-80 flex flex-col gap-2 rounded-md bg-slate-800 p-2 shadow-lg">
+            <div class="flex flex-row items-center gap-1 font-sans text-xs text-white">
+                <select
+                    on:change=move |ev| set_expiry_idx.set(event_target_value(&ev).parse().unwrap_or_default())
+                    class="rounded bg-white/20 px-1 py-1 text-white border-0 focus:outline-none"
+                >
+                    {EXPIRY_OPTIONS
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, (label, _))| view! {
+                            <option value=idx.to_string() selected=move || expiry_idx.get() == idx>{*label}</option>
+                        })
+                        .collect_view()}
+                </select>
+                <select
+                    on:change=move |ev| set_max_uses_idx.set(event_target_value(&ev).parse().unwrap_or_default())
+                    class="rounded bg-white/20 px-1 py-1 text-white border-0 focus:outline-none"
+                >
+                    {MAX_USES_OPTIONS
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, (label, _))| view! {
+                            <option value=idx.to_string() selected=move || max_uses_idx.get() == idx>{*label}</option>
+                        })
+                        .collect_view()}
+                </select>
+                <button
+                    on:click=create_invite
+                    disabled=move || create_action.pending().get()
+                    class="ml-auto rounded-md px-2 py-1 text-xs text-white hover:text-black bg-sky-500 hover:bg-green-300 border-0"
+                >
+                    "Create link"
+                </button>
+            </div>
+            {move || create_error().map(|err| view! {
+                <p class="font-sans text-red-400 text-xs">{err}</p>
+            })}
+            <Transition fallback=|| ()>
+                <ul class="flex flex-col gap-1 max-h-72 overflow-y-auto">
+                    {move || {
+                        invites_resource
+                            .get()
+                            .and_then(Result::ok)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|invite| {
+                                let usable = invite.is_usable(Utc::now());
+                                let status = invite_status(&invite);
+                                let link = invite_link(&invite.code);
+                                let code = invite.code.clone();
+                                view! {
+                                    <li class="flex flex-col rounded bg-white/10 px-2 py-1">
+                                        <input
+                                            readonly
+                                            value=link
+                                            on:focus=move |ev| event_target::<web_sys::HtmlInputElement>(&ev).select()
+                                            class=if usable {
+                                                "bg-transparent border-0 p-0 font-mono text-xs text-white focus:outline-none"
+                                            } else {
+                                                "bg-transparent border-0 p-0 font-mono text-xs text-white/40 line-through focus:outline-none"
+                                            }
+                                        />
+                                        <div class="flex flex-row items-center justify-between">
+                                            <span class="font-sans text-[11px] text-white/[.65]">{status}</span>
+                                            {usable.then(|| view! {
+                                                <button
+                                                    on:click=move |_| revoke_action.dispatch(RevokeInvite { code: code.clone() })
+                                                    class="font-sans text-[11px] text-red-300 hover:text-red-200 border-none bg-transparent"
+                                                >
+                                                    "revoke"
+                                                </button>
+                                            })}
+                                        </div>
+                                    </li>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </ul>
+            </Transition>
+        </div>
+    }
+}
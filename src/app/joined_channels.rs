@@ -1,75 +1,212 @@
+use super::chat::WebsocketCtx;
+use crate::{
+    models::{frame_model::ServerFrame, read_marker_model::UnreadCount, user_model::User},
+    state::rooms_manager::RoomKind,
+};
 use leptos::*;
 use leptos_router::A;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-type ChannelsResource = Resource<(usize, usize), Result<Vec<JoinedChannel>, ServerFnError>>;
+pub type ChannelsResource = Resource<(usize, usize), Result<Vec<JoinedChannel>, ServerFnError>>;
 
+// every room the user is in, direct conversations included
 #[derive(Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct JoinedChannel {
-    uuid: String,
-    name: String,
+    pub uuid: String,
+    pub name: String,
+    pub kind: RoomKind,
+    pub unread: UnreadCount,
 }
 
 #[cfg(feature = "ssr")]
 impl JoinedChannel {
-    fn new(uuid: String, name: String) -> Self {
-        Self { uuid, name }
+    fn new(uuid: String, name: String, kind: RoomKind, unread: UnreadCount) -> Self {
+        Self {
+            uuid,
+            name,
+            kind,
+            unread,
+        }
     }
 }
 
-// test
-
 #[server(FetchJoinedChannels, "/api", "GetJson")]
 pub async fn fetch_joined_channels() -> Result<Vec<JoinedChannel>, ServerFnError> {
     use crate::{
         error::ServerError,
-        models::user_model::UserData,
-        state::{auth, pool, rooms_manager},
+        state::{auth, repository, rooms_manager},
     };
     use futures::future::join_all;
 
     let auth = auth()?;
-    let pool = pool()?;
+    let repository = repository()?;
     let rooms_manager = rooms_manager()?;
     let current_user = auth
         .current_user
         .ok_or_else(|| ServerFnError::new("There is no current user!"))?;
-    let user_data = UserData::get_from_uuid(&current_user.uuid, &pool)
-        .await
+    let user_data = repository
+        .users
+        .get_user_by_uuid(&current_user.uuid)
+        .await?
         .ok_or_else(|| ServerFnError::new("Invalid user: Entry not found in db"))?;
-    let joined_channels = user_data
-        .joined_channels
-        .iter()
-        .map(|room_uuid| async {
-            let room_name = rooms_manager.get_room_name(room_uuid, &pool).await?;
-            Ok::<JoinedChannel, ServerError>(JoinedChannel::new(room_uuid.clone(), room_name))
+    let joined_channels = user_data.joined_channels.iter().map(|room_uuid| async {
+        let room_data = rooms_manager.validate_uuid(room_uuid, &repository).await?;
+        let read_marker = repository
+            .read_markers
+            .get_read_marker(&user_data.uuid, room_uuid)
+            .await?;
+        let unread = repository
+            .messages
+            .count_unread(
+                room_uuid,
+                &user_data.uuid,
+                read_marker.map(|read_marker| read_marker.cursor()).as_ref(),
+            )
+            .await?;
+        Ok::<JoinedChannel, ServerError>(JoinedChannel::new(
+            room_uuid.clone(),
+            room_data.display_name(&user_data.uuid),
+            room_data.kind,
+            unread,
+        ))
+    });
+    // a room that fails to load is left out rather than shown without a uuid
+    let joined_channels = join_all(joined_channels)
+        .await
+        .into_iter()
+        .filter_map(|res| {
+            res.map_err(|err| logging::warn!("unable to load joined room: {}", err))
+                .ok()
         })
-        .map(|res| async { res.await.unwrap_or_default() });
-    let joined_channels = join_all(joined_channels).await;
+        .collect();
     Ok(joined_channels)
 }
 
+// --- unread counts of every joined room, seeded by the resource, then kept up to date from the pushed messages
+pub fn track_unread(channels_resource: ChannelsResource) -> RwSignal<HashMap<String, UnreadCount>> {
+    let ws_ctx = expect_context::<WebsocketCtx>();
+    let frame = ws_ctx.frame;
+    let user_resource = expect_context::<Resource<(), Result<User, ServerFnError>>>();
+    let path = leptos_router::use_location().pathname;
+    let is_viewing =
+        move |room_uuid: &str| path.with(|path| path == &format!("/channel/{}", room_uuid));
+
+    let unread_counts = create_rw_signal(HashMap::<String, UnreadCount>::new());
+    create_effect(move |_| {
+        if let Some(Ok(vec_channel)) = channels_resource.get() {
+            unread_counts.set(
+                vec_channel
+                    .into_iter()
+                    .map(|channel| (channel.uuid, channel.unread))
+                    .collect(),
+            );
+        }
+    });
+    create_effect(move |_| {
+        if let Some(ServerFrame::NewMessage { msg }) = frame.get() {
+            let Some(Ok(user)) = user_resource.get_untracked() else {
+                return;
+            };
+            // someone else started a conversation with the user, the list doesn't know it yet
+            let known = channels_resource.with_untracked(|result| {
+                matches!(result, Some(Ok(vec_channel)) if vec_channel.iter().any(|channel| channel.uuid == msg.channel))
+            });
+            if !known {
+                channels_resource.refetch();
+            }
+            let from_me = msg
+                .msg_sender
+                .as_ref()
+                .is_some_and(|sender| sender.uuid == user.uuid);
+            if from_me || msg.parent.is_some() || is_viewing(&msg.channel) {
+                return;
+            }
+            unread_counts.update(|counts| {
+                let count = counts.entry(msg.channel.clone()).or_default();
+                count.unread += 1;
+                if msg.mentions_user(&user.uuid) {
+                    count.mentions += 1;
+                }
+            });
+        }
+    });
+    // --- group conversations are named after their participants, channels get renamed or deleted
+    create_effect(move |_| {
+        if let Some(
+            ServerFrame::ParticipantsChanged { .. }
+            | ServerFrame::RoomUpdated { .. }
+            | ServerFrame::RoomDeleted { .. },
+        ) = frame.get()
+        {
+            channels_resource.refetch();
+        }
+    });
+    // --- opening a channel reads it
+    create_effect(move |_| {
+        let path = path.get();
+        if let Some(room_uuid) = path.strip_prefix("/channel/") {
+            unread_counts.update(|counts| {
+                counts.remove(room_uuid);
+            });
+        }
+    });
+    unread_counts
+}
+
+// the joined rooms of the given kinds, in the order the user joined them
+pub fn channels_of_kind(
+    channels_resource: ChannelsResource,
+    kinds: &[RoomKind],
+) -> Vec<JoinedChannel> {
+    channels_resource
+        .get()
+        .unwrap_or_else(|| Ok(Vec::<JoinedChannel>::new()))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|joined_channel| kinds.contains(&joined_channel.kind))
+        .collect()
+}
+
+#[component]
+pub fn UnreadBadge(unread: Signal<UnreadCount>) -> impl IntoView {
+    move || {
+        let unread = unread.get();
+        if unread.mentions > 0 {
+            Some(view! {
+                <span class="absolute -top-1 -right-1 min-w-5 h-5 px-1 rounded-full bg-red-500 text-white font-sans text-xs text-center leading-5">
+                    "@"{unread.mentions}
+                </span>
+            })
+        } else if unread.unread > 0 {
+            Some(view! {
+                <span class="absolute -top-1 -right-1 min-w-5 h-5 px-1 rounded-full bg-slate-200 text-slate-900 font-sans text-xs text-center leading-5">
+                    {unread.unread}
+                </span>
+            })
+        } else {
+            None
+        }
+    }
+}
+
 #[component]
-pub fn JoinedChannels(channels_resource: ChannelsResource) -> impl IntoView {
+pub fn JoinedChannels(
+    channels_resource: ChannelsResource,
+    unread_counts: RwSignal<HashMap<String, UnreadCount>>,
+) -> impl IntoView {
     view! {
         <For
-        {move || channels_resource.track()}
-            each=move || {
-                channels_resource
-                    .get()
-                    .unwrap_or_else(|| Ok(Vec::<JoinedChannel>::new()))
-                    .unwrap_or_default()
-                    .into_iter()
-                    .enumerate()
-            }
-            key=|(_, joined_channel)| joined_channel.uuid.clone()
-            children=move |(idx, _)| {
+            each=move || channels_of_kind(channels_resource, &[RoomKind::Channel])
+            key=|joined_channel| joined_channel.uuid.clone()
+            children=move |joined_channel| {
+                let uuid = joined_channel.uuid.clone();
                 let channel = create_memo(move |_| {
-                    channels_resource.and_then(|result| {
-                        result.get(idx).unwrap().clone()
-                    })
-                    .unwrap_or(Ok(JoinedChannel::default()))
-                    .unwrap_or_default()
+                    channels_resource
+                        .and_then(|result| result.iter().find(|known| known.uuid == uuid).cloned())
+                        .and_then(Result::ok)
+                        .flatten()
+                        .unwrap_or_default()
                 });
 
                 let room_uuid = channel.get().uuid;
@@ -82,11 +219,17 @@ pub fn JoinedChannels(channels_resource: ChannelsResource) -> impl IntoView {
                         "text-xl text-white text-ellipsis overflow-hidden uppercase w-12 h-12 rounded-xl bg-sky-500 hover:bg-green-300 border-none mt-2 px-2"
                     }};
 
+                let count_uuid = room_uuid.clone();
+                let unread = Signal::derive(move || {
+                    unread_counts.with(|counts| counts.get(&count_uuid).copied().unwrap_or_default())
+                });
+
                 view! {
-                    <A href=channel.get().uuid>
+                    <A href=channel.get().uuid class="relative block">
                         <button class={active(room_uuid)}>
                             { move || channel.get().name }
                         </button>
+                        <UnreadBadge unread/>
                     </A>
                 }
             }
@@ -7,14 +7,15 @@ type LoginAction = Action<UserLogin, Result<(), ServerFnError>>;
 #[server(UserLogin)]
 pub async fn login(email: String, password: String) -> Result<(), ServerFnError> {
     use super::AppPath;
-    use crate::models::user_model::UserData;
-    use crate::state::{auth, pool};
+    use crate::state::{auth, repository};
     use argon2::{Argon2, PasswordHash, PasswordVerifier};
 
-    let pool = pool()?;
+    let repository = repository()?;
     let auth = auth()?;
 
-    let user = UserData::get_from_email(&email, &pool)
+    let user = repository
+        .users
+        .get_user_by_email(&email)
         .await?
         .ok_or_else(|| ServerFnError::new("User does not exist"))?;
 
@@ -0,0 +1,241 @@
+use {
+    super::chat::WebsocketCtx,
+    crate::models::{
+        frame_model::ServerFrame,
+        presence_model::{MemberPresence, PresenceStatus},
+        role_model::{Permission, Role},
+        user_model::User,
+    },
+    leptos::*,
+};
+
+#[server(SetRole)]
+pub async fn set_role(
+    room_uuid: String,
+    user_uuid: String,
+    role: Role,
+) -> Result<(), ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    rooms_manager
+        .set_role(&room_uuid, &user, &user_uuid, role, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))
+}
+
+#[server(KickMember)]
+pub async fn kick_member(
+    room_uuid: String,
+    user_uuid: String,
+    ban: bool,
+) -> Result<(), ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    rooms_manager
+        .kick(&room_uuid, &user, &user_uuid, ban, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))
+}
+
+#[server(FetchRoomPresence, "/api", "GetJson")]
+pub async fn fetch_room_presence(room_uuid: String) -> Result<Vec<MemberPresence>, ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let room_data = rooms_manager
+        .member_room(&room_uuid, &user.uuid, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))?;
+
+    let mut vec_member = room_data
+        .users
+        .values()
+        .map(|user| MemberPresence {
+            status: rooms_manager.presence.status(&user.uuid),
+            role: room_data.role_of(&user.uuid).unwrap_or_default(),
+            user: user.clone(),
+        })
+        .collect::<Vec<MemberPresence>>();
+    vec_member.sort_by(|a, b| (a.status, &a.user.user_name).cmp(&(b.status, &b.user.user_name)));
+    Ok(vec_member)
+}
+
+#[component]
+pub fn MembersPanel(room_uuid: String) -> impl IntoView {
+    let ws_ctx = expect_context::<WebsocketCtx>();
+    let frame = ws_ctx.frame;
+    let user_resource = expect_context::<Resource<(), Result<User, ServerFnError>>>();
+    let current_room = room_uuid.clone();
+    let action_room = store_value(room_uuid.clone());
+    let presence_resource = create_resource(move || room_uuid.clone(), fetch_room_presence);
+    // --- the resource only seeds the list, changes are pushed by the server
+    let members = create_rw_signal(Vec::<MemberPresence>::new());
+    create_effect(move |_| {
+        if let Some(Ok(vec_member)) = presence_resource.get() {
+            members.set(vec_member);
+        }
+    });
+    create_effect(move |_| {
+        if let Some(ServerFrame::Presence { user_uuid, status }) = frame.get() {
+            members.update(|vec| {
+                if let Some(member) = vec.iter_mut().find(|member| member.user.uuid == user_uuid) {
+                    member.status = status;
+                }
+                vec.sort_by(|a, b| {
+                    (a.status, &a.user.user_name).cmp(&(b.status, &b.user.user_name))
+                });
+            });
+        }
+    });
+    create_effect(move |_| {
+        if let Some(ServerFrame::RoleChanged {
+            room_uuid,
+            user_uuid,
+            role,
+        }) = frame.get()
+        {
+            if room_uuid != current_room {
+                return;
+            }
+            members.update(|vec| {
+                if let Some(member) = vec.iter_mut().find(|member| member.user.uuid == user_uuid) {
+                    member.role = role;
+                }
+            });
+        }
+    });
+    let removed_room = action_room.get_value();
+    create_effect(move |_| {
+        if let Some(ServerFrame::MemberRemoved {
+            room_uuid,
+            user_uuid,
+            ..
+        }) = frame.get()
+        {
+            if room_uuid == removed_room {
+                members.update(|vec| vec.retain(|member| member.user.uuid != user_uuid));
+            }
+        }
+    });
+    // --- the owner appoints and dismisses admins, moderators remove those below them
+    let set_role_action = create_server_action::<SetRole>();
+    let kick_action = create_server_action::<KickMember>();
+    let my_role = move || {
+        let current_uuid = user_resource
+            .get()
+            .and_then(Result::ok)
+            .map(|user| user.uuid)
+            .unwrap_or_default();
+        members.with(|vec| {
+            vec.iter()
+                .find(|member| member.user.uuid == current_uuid)
+                .map(|member| member.role)
+        })
+    };
+    let online_count = move || {
+        members.with(|vec| {
+            vec.iter()
+                .filter(|member| member.status != PresenceStatus::Offline)
+                .count()
+        })
+    };
+
+    view! {
+        <div class="shrink-0 flex flex-col w-[200px] h-full bg-slate-900/[.35] rounded-r-xl">
+            <p class="h-[50px] shrink-0 flex items-center px-3 font-sans text-white tracking-wider">
+                "Members "
+                <span class="ml-1 text-white/[.65] text-xs">
+                    {online_count}" / "{move || members.with(Vec::len)}" online"
+                </span>
+            </p>
+            <ul class="flex flex-col grow px-3 overflow-y-scroll">
+                {move || {
+                    members
+                        .get()
+                        .into_iter()
+                        .map(|member| {
+                            let (dot_class, label) = match member.status {
+                                PresenceStatus::Online => ("bg-green-400", "online"),
+                                PresenceStatus::Idle => ("bg-amber-400", "idle"),
+                                PresenceStatus::Offline => ("bg-slate-500", "offline"),
+                            };
+                            let role_label = (member.role != Role::Member).then(|| member.role.label());
+                            // only admins and plain members can be promoted or dismissed
+                            let next_role = match member.role {
+                                Role::Owner => None,
+                                Role::Admin => Some((Role::Member, "dismiss")),
+                                Role::Member => Some((Role::Admin, "make admin")),
+                            };
+                            let can_set_role = my_role() == Some(Role::Owner);
+                            let can_kick = my_role().is_some_and(|my_role| my_role.can(Permission::Kick) && member.role > my_role);
+                            let user_uuid = store_value(member.user.uuid.clone());
+                            let kick = move |ban: bool| {
+                                kick_action.dispatch(KickMember {
+                                    room_uuid: action_room.get_value(),
+                                    user_uuid: user_uuid.get_value(),
+                                    ban,
+                                })
+                            };
+                            view! {
+                                <li class="group flex flex-row items-center py-1" title=label>
+                                    <span class=format!("size-2 rounded-full mr-2 {}", dot_class)></span>
+                                    <span class="font-sans text-white text-sm truncate">
+                                        {member.user.user_name}
+                                    </span>
+                                    {role_label.map(|role_label| view! {
+                                        <span class="ml-1 font-sans text-white/[.65] text-xs">{role_label}</span>
+                                    })}
+                                    <span class="ml-auto hidden group-hover:flex flex-row gap-1">
+                                        {next_role.filter(|_| can_set_role).map(|(role, action)| view! {
+                                            <button
+                                                on:click=move |_| set_role_action.dispatch(SetRole {
+                                                    room_uuid: action_room.get_value(),
+                                                    user_uuid: user_uuid.get_value(),
+                                                    role,
+                                                })
+                                                class="font-sans text-white/[.65] text-xs hover:text-white border-none bg-transparent"
+                                            >
+                                                {action}
+                                            </button>
+                                        })}
+                                        <Show when=move || can_kick>
+                                            <button
+                                                on:click=move |_| kick(false)
+                                                class="font-sans text-white/[.65] text-xs hover:text-white border-none bg-transparent"
+                                            >
+                                                "kick"
+                                            </button>
+                                            <button
+                                                on:click=move |_| kick(true)
+                                                class="font-sans text-red-300 text-xs hover:text-red-400 border-none bg-transparent"
+                                            >
+                                                "ban"
+                                            </button>
+                                        </Show>
+                                    </span>
+                                </li>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </ul>
+        </div>
+    }
+}
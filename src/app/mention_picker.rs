@@ -0,0 +1,77 @@
+use {crate::models::user_model::User, leptos::*};
+
+const MAX_SUGGESTIONS: usize = 5;
+
+// the `@prefix` the composer text ends with, none once a space follows it
+pub fn pending_mention(text: &str) -> Option<&str> {
+    let idx = text.rfind('@')?;
+    let starts_word = text[..idx]
+        .chars()
+        .next_back()
+        .is_none_or(char::is_whitespace);
+    let prefix = &text[idx + 1..];
+    (starts_word && !prefix.contains(char::is_whitespace)).then_some(prefix)
+}
+
+// swaps the pending `@prefix` for the full name of the picked member
+pub fn complete_mention(text: &str, user_name: &str) -> String {
+    let prefix_len = pending_mention(text).map_or(0, |prefix| prefix.len() + 1);
+    format!("{}@{} ", &text[..text.len() - prefix_len], user_name)
+}
+
+pub fn suggestions(members: &[User], prefix: &str) -> Vec<User> {
+    let prefix = prefix.to_lowercase();
+    let mut vec_user = members
+        .iter()
+        .filter(|user| user.user_name.to_lowercase().starts_with(&prefix))
+        .cloned()
+        .collect::<Vec<User>>();
+    vec_user.sort_by(|a, b| a.user_name.cmp(&b.user_name));
+    vec_user.truncate(MAX_SUGGESTIONS);
+    vec_user
+}
+
+// rewriting the text of a contenteditable puts the caret back at its start
+pub fn place_caret_at_end(node: &web_sys::HtmlElement) {
+    let Ok(Some(selection)) = window().get_selection() else {
+        return;
+    };
+    let Ok(range) = document().create_range() else {
+        return;
+    };
+    if range.select_node_contents(node).is_ok() {
+        range.collapse_with_to_start(false);
+        let _ = selection.remove_all_ranges();
+        let _ = selection.add_range(&range);
+    }
+}
+
+#[component]
+pub fn MentionPicker(suggestions: Memo<Vec<User>>, on_pick: Callback<User>) -> impl IntoView {
+    view! {
+        <Show when=move || !suggestions.get().is_empty()>
+            <ul class="mx-4 mb-1 w-64 rounded-md bg-white shadow-lg overflow-hidden">
+                <For
+                    each=move || suggestions.get()
+                    key=|user| user.uuid.clone()
+                    children=move |user| {
+                        let picked = user.clone();
+                        view! {
+                            // mousedown keeps the focus in the composer, which would reset on blur
+                            <li
+                                on:mousedown=move |ev| {
+                                    ev.prevent_default();
+                                    on_pick.call(picked.clone());
+                                }
+                                class="px-3 py-1 cursor-pointer font-sans text-sm text-black hover:bg-indigo-100"
+                            >
+                                "@"{user.user_name}
+                            </li>
+                        }
+                    }
+                />
+                <li class="px-3 py-0.5 font-sans text-xs text-black/50">"tab to complete"</li>
+            </ul>
+        </Show>
+    }
+}
@@ -0,0 +1,59 @@
+use {
+    super::chat::WebsocketCtx,
+    crate::models::{frame_model::ServerFrame, message_model::MsgResponse},
+    leptos::*,
+    leptos_router::A,
+    std::time::Duration,
+};
+
+const TOAST_TTL: Duration = Duration::from_secs(8);
+
+// pops up when someone mentions the user in a room they aren't looking at
+#[component]
+pub fn MentionToasts() -> impl IntoView {
+    let frame = expect_context::<WebsocketCtx>().frame;
+    let path = leptos_router::use_location().pathname;
+    let toasts = create_rw_signal(Vec::<MsgResponse>::new());
+    let dismiss = move |msg_uuid: String| {
+        toasts.update(|toasts| toasts.retain(|toast| toast.msg_uuid != msg_uuid));
+    };
+    create_effect(move |_| {
+        if let Some(ServerFrame::Mention { msg }) = frame.get() {
+            let viewing = path.with_untracked(|path| path == &format!("/channel/{}", msg.channel));
+            if viewing
+                || toasts.with_untracked(|toasts| {
+                    toasts.iter().any(|toast| toast.msg_uuid == msg.msg_uuid)
+                })
+            {
+                return;
+            }
+            let msg_uuid = msg.msg_uuid.clone();
+            toasts.update(|toasts| toasts.push(msg));
+            set_timeout(move || dismiss(msg_uuid), TOAST_TTL);
+        }
+    });
+
+    view! {
+        <div class="absolute right-4 bottom-4 z-20 flex flex-col gap-2 w-80">
+            <For
+                each=move || toasts.get()
+                key=|toast| toast.msg_uuid.clone()
+                children=move |toast| {
+                    let msg_uuid = toast.msg_uuid.clone();
+                    let sender = toast.msg_sender.clone().unwrap_or_default().user_name;
+                    view! {
+                        <div on:click=move |_| dismiss(msg_uuid.clone())>
+                            <A
+                                href=format!("/channel/{}", toast.channel)
+                                class="block rounded-md bg-white px-3 py-2 shadow-lg font-sans text-black"
+                            >
+                                <p class="text-xs text-black/60">{sender}" mentioned you"</p>
+                                <p class="text-sm truncate">{toast.message}</p>
+                            </A>
+                        </div>
+                    }
+                }
+            />
+        </div>
+    }
+}
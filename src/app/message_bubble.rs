@@ -0,0 +1,439 @@
+use {
+    super::{chat::WebsocketCtx, thread_panel::ThreadCtx},
+    crate::models::{
+        frame_model::ClientFrame,
+        mention_model::{segments, TextSegment},
+        message_model::{MsgResponse, MsgRevision},
+        reaction_model::QUICK_REACTIONS,
+        role_model::{Permission, Role},
+        user_model::User,
+    },
+    chrono::Local,
+    leptos::*,
+};
+
+#[server(EditMsg)]
+pub async fn edit_msg(msg_uuid: String, text: String) -> Result<(), ServerFnError> {
+    use crate::{
+        error::ServerError,
+        models::{frame_model::ServerFrame, mention_model::Mention, message_model::MsgResponse},
+        state::{auth, repository, rooms_manager},
+    };
+    use chrono::Utc;
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(ServerFnError::new("Message can't be empty"));
+    }
+    let mut msg_data = repository
+        .messages
+        .get_msg(&msg_uuid)
+        .await?
+        .filter(|msg_data| !msg_data.is_deleted())
+        .ok_or_else(|| ServerFnError::new(ServerError::MessageDoesNotExist))?;
+    if msg_data.msg_sender != user.uuid {
+        return Err(ServerFnError::new(ServerError::NotMessageSender));
+    }
+    // the same rules as posting, former members and archived rooms are read only
+    let room_data = rooms_manager
+        .member_room(&msg_data.channel, &user.uuid, &repository)
+        .await?;
+    if room_data.archived {
+        return Err(ServerFnError::new(ServerError::RoomArchived));
+    }
+    if msg_data.message == text {
+        return Ok(());
+    }
+
+    let edited_at = Utc::now();
+    repository
+        .messages
+        .insert_revision(&MsgRevision::new(&msg_data, edited_at))
+        .await?;
+    // only members the edit newly mentions get notified
+    let mentions = Mention::parse(&text, room_data.users.values());
+    let previous_mentions = std::mem::replace(&mut msg_data.mentions, mentions);
+    msg_data.message = text;
+    msg_data.edited_at = Some(edited_at);
+    repository.messages.update_msg(&msg_data).await?;
+
+    let msg = MsgResponse::new(&msg_data, Some(user));
+    rooms_manager.notify_mentions(
+        &msg,
+        msg.mentions
+            .iter()
+            .filter(|mention| !previous_mentions.contains(mention)),
+    );
+    rooms_manager.broadcast(&msg_data.channel, ServerFrame::MessageEdited { msg });
+    Ok(())
+}
+
+#[server(DeleteMsg)]
+pub async fn delete_msg(msg_uuid: String) -> Result<(), ServerFnError> {
+    use crate::{
+        error::ServerError,
+        models::{frame_model::ServerFrame, role_model::Permission},
+        state::{auth, repository, rooms_manager},
+    };
+    use chrono::Utc;
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let mut msg_data = repository
+        .messages
+        .get_msg(&msg_uuid)
+        .await?
+        .filter(|msg_data| !msg_data.is_deleted())
+        .ok_or_else(|| ServerFnError::new(ServerError::MessageDoesNotExist))?;
+    // the same rules as editing, former members and archived rooms are read only
+    let room_data = rooms_manager
+        .member_room(&msg_data.channel, &user.uuid, &repository)
+        .await?;
+    if room_data.archived {
+        return Err(ServerFnError::new(ServerError::RoomArchived));
+    }
+    // --- the sender can delete their own message, moderators can delete any of them
+    if msg_data.msg_sender != user.uuid {
+        room_data.require(&user.uuid, Permission::DeleteMessages)?;
+    }
+
+    msg_data.tombstone(user.uuid, Utc::now());
+    repository.messages.tombstone_msg(&msg_data).await?;
+    rooms_manager.broadcast(
+        &msg_data.channel,
+        ServerFrame::MessageDeleted {
+            msg: msg_data.into_msg_response(repository.users.as_ref()).await,
+        },
+    );
+    Ok(())
+}
+
+#[server(FetchMsgRevisions, "/api", "GetJson")]
+pub async fn fetch_msg_revisions(msg_uuid: String) -> Result<Vec<MsgRevision>, ServerFnError> {
+    use crate::{
+        error::ServerError,
+        state::{auth, repository, rooms_manager},
+    };
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    let msg_data = repository
+        .messages
+        .get_msg(&msg_uuid)
+        .await?
+        .ok_or_else(|| ServerFnError::new(ServerError::MessageDoesNotExist))?;
+    rooms_manager
+        .member_room(&msg_data.channel, &user.uuid, &repository)
+        .await?;
+
+    Ok(repository.messages.get_revisions(&msg_uuid).await?)
+}
+
+#[component]
+pub fn MessageBubble(
+    // not a memo, equal messages can still differ in their text or reactions
+    #[prop(into)] msg: Signal<MsgResponse>,
+    user_resource: Resource<(), Result<User, ServerFnError>>,
+    // role of the current user in the room, moderators may delete anyone's message
+    #[prop(into)] role: Signal<Role>,
+    // bubbles inside the thread panel can't open a thread themselves
+    #[prop(optional)] in_thread: bool,
+    // members who have read up to this message, only set on the latest own message
+    #[prop(optional)] seen_by: Option<Memo<Vec<User>>>,
+) -> impl IntoView {
+    let seen_by = move || seen_by.map(|seen_by| seen_by.get()).unwrap_or_default();
+    let thread_ctx = expect_context::<ThreadCtx>();
+    let handle_open_thread = move |_: ev::MouseEvent| {
+        thread_ctx.open(msg.get_untracked(), role.get_untracked());
+    };
+    let replies_label = move || match msg.get().reply_count {
+        0 => "reply".to_string(),
+        1 => "1 reply".to_string(),
+        count => format!("{} replies", count),
+    };
+    let current_uuid = move || {
+        user_resource
+            .map(|user| user.clone().unwrap_or_default().uuid)
+            .unwrap_or_default()
+    };
+    let sender = move || msg.get().msg_sender.unwrap_or_default().uuid == current_uuid();
+    let can_delete =
+        move || !msg.get().is_deleted() && (sender() || role.get().can(Permission::DeleteMessages));
+    let deleted_note = move || {
+        let msg = msg.get();
+        if msg.deleted_by.is_some() && msg.deleted_by != msg.msg_sender.map(|user| user.uuid) {
+            "message deleted by a moderator"
+        } else {
+            "message deleted"
+        }
+    };
+    let receiver_class = "bg-transparent flex flex-row mt-2";
+    let sender_class = "bg-transparent flex flex-row-reverse mt-2";
+
+    let (editing, set_editing) = create_signal(false);
+    let (show_history, set_show_history) = create_signal(false);
+    let edit_action = create_server_action::<EditMsg>();
+    let delete_action = create_server_action::<DeleteMsg>();
+    let edit_input = create_node_ref::<html::Input>();
+    let history_resource = create_resource(
+        move || show_history.get().then(|| msg.get().msg_uuid),
+        |maybe_uuid| async move {
+            match maybe_uuid {
+                Some(msg_uuid) => fetch_msg_revisions(msg_uuid).await,
+                None => Ok(Vec::new()),
+            }
+        },
+    );
+
+    let handle_edit_keyup = move |ev: ev::KeyboardEvent| match ev.key().as_str() {
+        "Enter" => {
+            let text = edit_input
+                .get()
+                .expect("input element doesn't exist")
+                .value();
+            if !text.trim().is_empty() {
+                edit_action.dispatch(EditMsg {
+                    msg_uuid: msg.get_untracked().msg_uuid,
+                    text,
+                });
+            }
+            set_editing.set(false);
+        }
+        "Escape" => set_editing.set(false),
+        _ => {}
+    };
+    let ws_ctx = expect_context::<WebsocketCtx>();
+    let (show_picker, set_show_picker) = create_signal(false);
+    let toggle_reaction = Callback::new(move |emoji: String| {
+        ws_ctx.send_frame(&ClientFrame::ToggleReaction {
+            msg_uuid: msg.get_untracked().msg_uuid,
+            emoji,
+        });
+    });
+    let reaction_chips = move || {
+        let current_uuid = current_uuid();
+        msg.get()
+            .reactions
+            .into_iter()
+            .map(|summary| {
+                let chip_class = if summary.reacted_by(&current_uuid) {
+                    "flex flex-row items-center px-1 rounded-full text-xs border border-indigo-500 bg-indigo-100"
+                } else {
+                    "flex flex-row items-center px-1 rounded-full text-xs border border-black/[.15] bg-white/40 hover:bg-white/70"
+                };
+                let reacted_by = summary
+                    .users
+                    .iter()
+                    .map(|user| user.user_name.clone())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let emoji = summary.emoji.clone();
+                view! {
+                    <button
+                        on:click=move |_| toggle_reaction.call(emoji.clone())
+                        title=reacted_by
+                        class=chip_class
+                    >
+                        <span>{summary.emoji}</span>
+                        <span class="ml-1 font-sans text-black/[.65]">{summary.count}</span>
+                    </button>
+                }
+            })
+            .collect_view()
+    };
+
+    let handle_delete = move |_: ev::MouseEvent| {
+        delete_action.dispatch(DeleteMsg {
+            msg_uuid: msg.get_untracked().msg_uuid,
+        });
+    };
+
+    view! {
+        <li class=move || if sender() { sender_class } else { receiver_class }>
+            <div class="flex flex-shrink-0 justify-center items-center pb-1 size-9 bg-sky-500 rounded-full text-white hover:text-black hover:bg-green-300 uppercase font-sans text-2xl text-center">
+                {move || msg.get().msg_sender.unwrap_or_default().avatar.get_view()}
+            </div>
+            <div class=move || if sender() { "flex flex-col mr-2 rounded-l-lg rounded-br-lg bg-green-300 px-2 max-w-[500px]" } else { "flex flex-col ml-2 rounded-r-lg rounded-bl-lg bg-slate-300 px-2 max-w-[500px]" }>
+                <div class=move || if sender() { "flex flex-row flex-wrap justify-end" } else { "flex flex-row content-start" }>
+                    {move || if sender() {
+                        view! {
+                            <p>
+                                <span class="font-sans text-black/[.65] text-xs mr-2">
+                                    {move || msg.get().created_at.with_timezone(&Local).format("%d/%m/%Y %H:%M").to_string()}
+                                </span>
+                                <span class="font-sans text-indigo-500 text-lg">
+                                    {move || msg.get().msg_sender.unwrap_or_default().user_name}
+                                </span>
+                            </p>
+                        }
+                    } else {
+                        view! {
+                            <p>
+                                <span class="font-sans text-indigo-500 text-lg">
+                                    {move || msg.get().msg_sender.unwrap_or_default().user_name}
+                                </span>
+                                <span class="font-sans text-black/[.65] text-xs ml-2">
+                                    {move || msg.get().created_at.with_timezone(&Local).format("%d/%m/%Y %H:%M").to_string()}
+                                </span>
+                            </p>
+                        }
+                    }}
+                </div>
+                <div class=move || if sender() { "flex flex-row flex-wrap justify-end" } else { "flex flex-row flex-wrap justify-start" }>
+                    {move || if msg.get().is_deleted() {
+                        view! {
+                            <p class="py-1 font-sans text-black/[.65] text-sm italic">
+                                {deleted_note}
+                            </p>
+                        }.into_view()
+                    } else if editing.get() {
+                        view! {
+                            <input
+                                _ref=edit_input
+                                on:keyup=handle_edit_keyup
+                                value=msg.get_untracked().message
+                                class="my-1 pl-1 font-sans text-black bg-white/60 focus:outline-none border-0 w-full text-base"
+                            />
+                        }.into_view()
+                    } else {
+                        view! {
+                            <pre class="py-1 font-sans text-black text text-wrap">
+                                {move || {
+                                    let msg = msg.get();
+                                    segments(&msg.message, &msg.mentions)
+                                        .into_iter()
+                                        .map(|segment| match segment {
+                                            TextSegment::Plain(text) => text.into_view(),
+                                            TextSegment::Mention(mention) => {
+                                                let class = if mention.user_uuid == current_uuid() {
+                                                    "px-0.5 rounded bg-amber-200 text-amber-900 font-semibold"
+                                                } else {
+                                                    "px-0.5 rounded bg-indigo-100 text-indigo-700 font-semibold"
+                                                };
+                                                view! {
+                                                    <span class=class>"@"{mention.user_name}</span>
+                                                }.into_view()
+                                            }
+                                        })
+                                        .collect_view()
+                                }}
+                            </pre>
+                        }.into_view()
+                    }}
+                </div>
+                <Show when=move || !msg.get().is_deleted()>
+                    <div class=move || if sender() { "flex flex-row flex-wrap justify-end gap-1 pb-1" } else { "flex flex-row flex-wrap justify-start gap-1 pb-1" }>
+                        {reaction_chips}
+                        <button
+                            on:click=move |_| set_show_picker.update(|show| *show = !*show)
+                            title="Add a reaction"
+                            class="px-1 rounded-full font-sans text-black/[.65] text-xs border border-black/[.15] bg-transparent hover:bg-white/40"
+                        >
+                            "+"
+                        </button>
+                    </div>
+                    <Show when=move || show_picker.get()>
+                        <div class="flex flex-row gap-1 pb-1">
+                            {QUICK_REACTIONS
+                                .iter()
+                                .map(|emoji| view! {
+                                    <button
+                                        on:click=move |_| {
+                                            toggle_reaction.call(emoji.to_string());
+                                            set_show_picker.set(false);
+                                        }
+                                        class="text-base border-none bg-transparent hover:scale-125"
+                                    >
+                                        {*emoji}
+                                    </button>
+                                })
+                                .collect_view()}
+                        </div>
+                    </Show>
+                </Show>
+                <div class=move || if sender() { "flex flex-row justify-end space-x-2 pb-1" } else { "flex flex-row justify-start space-x-2 pb-1" }>
+                    <Show when=move || msg.get().edited_at.is_some() && !msg.get().is_deleted()>
+                        <button
+                            on:click=move |_| set_show_history.update(|show| *show = !*show)
+                            class="font-sans text-black/[.65] text-xs hover:text-indigo-500 border-none bg-transparent"
+                        >
+                            "(edited)"
+                        </button>
+                    </Show>
+                    <Show when=move || !in_thread && (msg.get().reply_count > 0 || !msg.get().is_deleted())>
+                        <button
+                            on:click=handle_open_thread.clone()
+                            class="font-sans text-black/[.65] text-xs hover:text-indigo-500 border-none bg-transparent"
+                        >
+                            {replies_label}
+                        </button>
+                    </Show>
+                    <Show when=move || sender() && !msg.get().is_deleted()>
+                        <button
+                            on:click=move |_| set_editing.update(|edit| *edit = !*edit)
+                            class="font-sans text-black/[.65] text-xs hover:text-indigo-500 border-none bg-transparent"
+                        >
+                            "edit"
+                        </button>
+                    </Show>
+                    <Show when=can_delete>
+                        <button
+                            on:click=handle_delete
+                            class="font-sans text-black/[.65] text-xs hover:text-red-600 border-none bg-transparent"
+                        >
+                            "delete"
+                        </button>
+                    </Show>
+                </div>
+                <Show when=move || !seen_by().is_empty()>
+                    <p
+                        class="self-end pb-1 font-sans text-black/[.65] text-xs cursor-default"
+                        title=move || {
+                            seen_by()
+                                .into_iter()
+                                .map(|user| user.user_name)
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        }
+                    >
+                        {move || format!("seen by {}", seen_by().len())}
+                    </p>
+                </Show>
+                <Show when=move || show_history.get()>
+                    <ol class="border-t border-black/[.15] pb-1">
+                        <Transition fallback=|| view! { <li class="font-sans text-xs">"Loading..."</li> }>
+                            {move || history_resource.get().map(|result| match result {
+                                Ok(vec_revision) => vec_revision
+                                    .into_iter()
+                                    .map(|revision| view! {
+                                        <li class="font-sans text-black/[.65] text-xs">
+                                            <span class="mr-2">
+                                                {revision.edited_at.with_timezone(&Local).format("%d/%m/%Y %H:%M").to_string()}
+                                            </span>
+                                            <span class="line-through">{revision.message}</span>
+                                        </li>
+                                    })
+                                    .collect_view(),
+                                Err(err) => view! { <li class="font-sans text-red-600 text-xs">{err.to_string()}</li> }.into_view(),
+                            })}
+                        </Transition>
+                    </ol>
+                </Show>
+            </div>
+        </li>
+    }
+}
@@ -0,0 +1,188 @@
+use {
+    super::message_bubble::MessageBubble,
+    crate::models::{
+        message_model::{MsgCursor, MsgResponse},
+        role_model::Role,
+        user_model::User,
+    },
+    leptos::*,
+    std::collections::HashMap,
+};
+
+// starting guess for a row height in px, refined by measuring the rendered rows
+const ESTIMATED_ROW_HEIGHT: f64 = 80.0;
+// rows rendered above and below the visible window
+const OVERSCAN: usize = 8;
+// distance from the top in px at which older messages are requested
+const LOAD_THRESHOLD: i32 = 200;
+// distance from the bottom in px still considered "at the bottom"
+const STICK_THRESHOLD: i32 = 40;
+
+#[component]
+pub fn MessageList(
+    // oldest first
+    messages: RwSignal<Vec<MsgResponse>>,
+    has_more: RwSignal<bool>,
+    loading: RwSignal<bool>,
+    load_older: Callback<MsgCursor>,
+    user_resource: Resource<(), Result<User, ServerFnError>>,
+    role: Signal<Role>,
+    // the "new messages" divider is drawn above this message
+    first_unread: RwSignal<Option<String>>,
+    // the latest message of the current user and who has read it
+    seen_by: Memo<Option<(String, Vec<User>)>>,
+) -> impl IntoView {
+    let list_ref = create_node_ref::<html::Div>();
+    let rows_ref = create_node_ref::<html::Ol>();
+    let scroll_top = create_rw_signal(0.0_f64);
+    let viewport = create_rw_signal(704.0_f64);
+    let row_height = create_rw_signal(ESTIMATED_ROW_HEIGHT);
+    let stick_to_bottom = create_rw_signal(true);
+    // distance from the bottom to restore once older messages are prepended
+    let anchor = create_rw_signal(None::<i32>);
+
+    let sync_scroll = move || {
+        if let Some(list) = list_ref.get_untracked() {
+            scroll_top.set(list.scroll_top() as f64);
+            viewport.set(list.client_height() as f64);
+            stick_to_bottom.set(
+                list.scroll_height() - list.scroll_top() - list.client_height() < STICK_THRESHOLD,
+            );
+        }
+    };
+
+    let handle_scroll = move |_: ev::Event| {
+        sync_scroll();
+        let Some(list) = list_ref.get_untracked() else {
+            return;
+        };
+        if list.scroll_top() < LOAD_THRESHOLD
+            && has_more.get_untracked()
+            && !loading.get_untracked()
+        {
+            if let Some(oldest) =
+                messages.with_untracked(|vec| vec.first().map(MsgCursor::from_msg))
+            {
+                anchor.set(Some(list.scroll_height() - list.scroll_top()));
+                load_older.call(oldest);
+            }
+        }
+    };
+
+    // --- keep the anchor when history is prepended, follow new messages when at the bottom
+    create_effect(move |_| {
+        messages.track();
+        if let Some(list) = list_ref.get_untracked() {
+            request_animation_frame(move || {
+                match anchor.get_untracked() {
+                    Some(offset) => {
+                        list.set_scroll_top(list.scroll_height() - offset);
+                        anchor.set(None);
+                    }
+                    None if stick_to_bottom.get_untracked() => {
+                        list.set_scroll_top(list.scroll_height());
+                    }
+                    None => {}
+                }
+                sync_scroll();
+            });
+        }
+    });
+
+    let window = create_memo(move |_| {
+        let len = messages.with(Vec::len);
+        let row = row_height.get();
+        let first = ((scroll_top.get() / row) as usize)
+            .saturating_sub(OVERSCAN)
+            .min(len);
+        let visible = (viewport.get() / row).ceil() as usize + 2 * OVERSCAN;
+        (first, (first + visible).min(len))
+    });
+
+    // --- refine the row estimate from what is actually on screen
+    create_effect(move |_| {
+        let (first, last) = window.get();
+        if let Some(rows) = rows_ref.get_untracked() {
+            request_animation_frame(move || {
+                let count = last.saturating_sub(first);
+                if count > 0 {
+                    let measured = rows.offset_height() as f64 / count as f64;
+                    if measured > 0.0 && (measured - row_height.get_untracked()).abs() > 4.0 {
+                        row_height.set(measured);
+                    }
+                }
+            });
+        }
+    });
+
+    // where each message sits, rebuilt once per update so the rows don't each scan the whole list
+    let positions = create_memo(move |_| {
+        messages.with(|vec| {
+            vec.iter()
+                .enumerate()
+                .map(|(idx, msg)| (msg.msg_uuid.clone(), idx))
+                .collect::<HashMap<String, usize>>()
+        })
+    });
+
+    let top_spacer = move || format!("height: {}px", window.get().0 as f64 * row_height.get());
+    let bottom_spacer = move || {
+        let len = messages.with(Vec::len);
+        format!(
+            "height: {}px",
+            len.saturating_sub(window.get().1) as f64 * row_height.get()
+        )
+    };
+
+    view! {
+        <div
+            class="flex flex-col h-[44rem] w-full bg-transparent px-4 overflow-y-scroll"
+            id="chat-log"
+            on:scroll=handle_scroll
+            _ref=list_ref
+        >
+            <Show when=move || loading.get()>
+                <p class="font-sans text-white/[.65] text-xs text-center py-2">"Loading..."</p>
+            </Show>
+            <div class="shrink-0" style=top_spacer></div>
+            <ol class="flex flex-col" _ref=rows_ref>
+                <For
+                    each=move || {
+                        let (first, last) = window.get();
+                        messages.with(|vec| vec[first..last].to_vec())
+                    }
+                    key=|msg_response| msg_response.msg_uuid.clone()
+                    children=move |msg_response| {
+                        let msg_uuid = msg_response.msg_uuid.clone();
+                        let divider_uuid = msg_uuid.clone();
+                        let is_first_unread = move || first_unread.with(|first| first.as_ref() == Some(&divider_uuid));
+                        let seen_uuid = msg_uuid.clone();
+                        let msg_seen_by = create_memo(move |_| {
+                            seen_by.with(|seen_by| match seen_by {
+                                Some((msg_uuid, users)) if *msg_uuid == seen_uuid => users.clone(),
+                                _ => Vec::new(),
+                            })
+                        });
+                        let msg = Signal::derive(move || {
+                            positions
+                                .with(|positions| positions.get(&msg_uuid).copied())
+                                .and_then(|idx| messages.with(|vec| vec.get(idx).cloned()))
+                                .unwrap_or_else(|| msg_response.clone())
+                        });
+                        view! {
+                            <Show when=is_first_unread>
+                                <li class="flex flex-row items-center my-2">
+                                    <span class="grow border-t border-red-400"></span>
+                                    <span class="px-2 font-sans text-red-400 text-xs">"new messages"</span>
+                                    <span class="grow border-t border-red-400"></span>
+                                </li>
+                            </Show>
+                            <MessageBubble msg user_resource role seen_by=msg_seen_by/>
+                        }
+                    }
+                />
+            </ol>
+            <div class="shrink-0" style=bottom_spacer></div>
+        </div>
+    }
+}
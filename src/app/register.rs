@@ -5,13 +5,13 @@ use leptos_router::{ActionForm, FromFormData, A};
 #[server(RegisterUser)]
 async fn register(user_name: String, email: String, password: String) -> Result<(), ServerFnError> {
     use super::AppPath;
-    use crate::{models::user_model::UserData, state::pool};
+    use crate::{models::user_model::UserData, state::repository};
     use argon2::{password_hash::SaltString, Argon2, PasswordHasher};
     use chrono::Utc;
     use rand_core::OsRng;
     use uuid::Uuid;
 
-    let pool = pool()?;
+    let repository = repository()?;
 
     // std::thread::sleep(std::time::Duration::from_millis(2000));
 
@@ -25,7 +25,7 @@ async fn register(user_name: String, email: String, password: String) -> Result<
     let created_at = Utc::now();
     let new_user = UserData::new(uuid, user_name, email.clone(), password, created_at);
 
-    match new_user.insert_into_db(&pool).await {
+    match repository.users.insert_user(&new_user).await {
         Ok(_) => {
             leptos_axum::redirect(&AppPath::Login.to_string());
             Ok(())
@@ -0,0 +1,161 @@
+use {
+    crate::models::{
+        role_model::{Permission, Role},
+        room_settings_model::{RoomSettings, DESCRIPTION_MAX, ROOM_NAME_MAX, TOPIC_MAX},
+    },
+    leptos::*,
+};
+
+#[server(UpdateRoomSettings)]
+pub async fn update_room_settings(
+    room_uuid: String,
+    settings: RoomSettings,
+) -> Result<(), ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    rooms_manager
+        .update_settings(&room_uuid, &user, settings, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))
+}
+
+// members are sent away by the `RoomDeleted` frame, the one deleting included
+#[server(DeleteRoom)]
+pub async fn delete_room(room_uuid: String) -> Result<(), ServerFnError> {
+    use crate::state::{auth, repository, rooms_manager};
+
+    let auth = auth()?;
+    let repository = repository()?;
+    let rooms_manager = rooms_manager()?;
+    let user = auth
+        .current_user
+        .ok_or_else(|| ServerFnError::new("Auth does not contain user"))?;
+    rooms_manager
+        .delete_room(&room_uuid, &user, &repository)
+        .await
+        .map_err(|err| ServerFnError::new(err))
+}
+
+// edits a draft of the settings, nothing changes for the others until it's saved
+#[component]
+pub fn RoomSettingsPanel(
+    room_uuid: String,
+    settings: RwSignal<RoomSettings>,
+    role: Signal<Role>,
+) -> impl IntoView {
+    let draft = create_rw_signal(settings.get_untracked());
+    // someone else saving while the panel is open replaces the draft
+    create_effect(move |_| draft.set(settings.get()));
+    let update_action = create_server_action::<UpdateRoomSettings>();
+    let delete_action = create_server_action::<DeleteRoom>();
+    let confirm_delete = create_rw_signal(false);
+    let update_room = room_uuid.clone();
+    let save = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        update_action.dispatch(UpdateRoomSettings {
+            room_uuid: update_room.clone(),
+            settings: draft.get_untracked(),
+        });
+    };
+    let delete = move |_: ev::MouseEvent| {
+        if confirm_delete.get_untracked() {
+            delete_action.dispatch(DeleteRoom {
+                room_uuid: room_uuid.clone(),
+            });
+        } else {
+            confirm_delete.set(true);
+        }
+    };
+    let error = move || {
+        update_action
+            .value()
+            .get()
+            .and_then(Result::err)
+            .or_else(|| delete_action.value().get().and_then(Result::err))
+            .map(|err| err.to_string())
+    };
+    let unchanged = move || draft.with(|draft| settings.with(|settings| draft == settings));
+
+    view! {
+        <div class="absolute right-2 top-[50px] z-10 w-80 flex flex-col gap-2 rounded-md bg-slate-800 p-3 shadow-lg font-sans text-sm text-white">
+            <form on:submit=save class="flex flex-col gap-2">
+                <label class="flex flex-col gap-1 text-xs text-white/[.65]">
+                    "Name"
+                    <input
+                        on:input=move |ev| draft.update(|draft| draft.room_name = event_target_value(&ev))
+                        prop:value=move || draft.with(|draft| draft.room_name.clone())
+                        maxlength=ROOM_NAME_MAX
+                        required
+                        class="rounded px-2 py-1 text-sm text-white bg-white/20 focus:outline-none border-0"
+                    />
+                </label>
+                <label class="flex flex-col gap-1 text-xs text-white/[.65]">
+                    "Topic"
+                    <input
+                        on:input=move |ev| draft.update(|draft| draft.topic = event_target_value(&ev))
+                        prop:value=move || draft.with(|draft| draft.topic.clone())
+                        maxlength=TOPIC_MAX
+                        placeholder="What's going on here right now"
+                        class="rounded px-2 py-1 text-sm text-white bg-white/20 focus:outline-none border-0"
+                    />
+                </label>
+                <label class="flex flex-col gap-1 text-xs text-white/[.65]">
+                    "Description"
+                    <textarea
+                        on:input=move |ev| draft.update(|draft| draft.description = event_target_value(&ev))
+                        prop:value=move || draft.with(|draft| draft.description.clone())
+                        maxlength=DESCRIPTION_MAX
+                        rows="3"
+                        placeholder="What the room is about, shown in the directory"
+                        class="rounded px-2 py-1 text-sm text-white bg-white/20 focus:outline-none border-0 resize-none"
+                    />
+                </label>
+                <label class="flex flex-row items-center gap-2 text-xs">
+                    <input
+                        type="checkbox"
+                        on:change=move |ev| draft.update(|draft| draft.public = event_target_checked(&ev))
+                        prop:checked=move || draft.with(|draft| draft.public)
+                    />
+                    "List in the room directory"
+                </label>
+                <label class="flex flex-row items-center gap-2 text-xs">
+                    <input
+                        type="checkbox"
+                        on:change=move |ev| draft.update(|draft| draft.archived = event_target_checked(&ev))
+                        prop:checked=move || draft.with(|draft| draft.archived)
+                    />
+                    "Archived, nobody can post and it leaves the directory"
+                </label>
+                <button
+                    disabled=move || unchanged() || update_action.pending().get()
+                    class="self-end rounded-md px-3 py-1 text-xs text-white hover:text-black bg-sky-500 hover:bg-green-300 disabled:opacity-40 border-0"
+                >
+                    "Save"
+                </button>
+            </form>
+            {move || error().map(|err| view! {
+                <p class="text-red-400 text-xs">{err}</p>
+            })}
+            <Show when=move || role.get().can(Permission::DeleteRoom)>
+                <div class="flex flex-row items-center justify-between border-t border-white/[.15] pt-2">
+                    <span class="text-xs text-white/[.65]">
+                        {move || if confirm_delete.get() { "Messages are lost for good" } else { "Delete the room for everyone" }}
+                    </span>
+                    <button
+                        on:click=delete.clone()
+                        disabled=move || delete_action.pending().get()
+                        class="rounded-md px-3 py-1 text-xs text-white bg-red-500 hover:bg-red-400 border-0"
+                    >
+                        {move || if confirm_delete.get() { "Confirm delete" } else { "Delete room" }}
+                    </button>
+                </div>
+            </Show>
+        </div>
+    }
+}
@@ -0,0 +1,244 @@
+use {
+    super::{
+        channel::{fetch_msg_page, PAGE_SIZE},
+        chat::WebsocketCtx,
+        message_bubble::MessageBubble,
+    },
+    crate::models::{
+        frame_model::{ClientFrame, ServerFrame},
+        message_model::{MsgCursor, MsgResponse},
+        role_model::Role,
+        user_model::User,
+    },
+    leptos::*,
+};
+
+// ---- the thread opened from a message bubble, shown in `#sub-channels`
+
+#[derive(Clone, Copy)]
+pub struct ThreadCtx {
+    pub parent: RwSignal<Option<MsgResponse>>,
+    // role of the current user in the room of the thread
+    pub role: RwSignal<Role>,
+}
+
+impl ThreadCtx {
+    pub fn new() -> Self {
+        Self {
+            parent: create_rw_signal(None),
+            role: create_rw_signal(Role::default()),
+        }
+    }
+
+    pub fn open(&self, parent: MsgResponse, role: Role) {
+        self.role.set(role);
+        self.parent.set(Some(parent));
+    }
+
+    pub fn close(&self) {
+        self.parent.set(None);
+    }
+}
+
+#[component]
+pub fn ThreadPanel() -> impl IntoView {
+    let thread_ctx = expect_context::<ThreadCtx>();
+    let path = leptos_router::use_location().pathname;
+    let parent = thread_ctx.parent;
+    // --- the thread belongs to its channel, leaving the channel closes it
+    create_effect(move |_| {
+        let path = path.get();
+        let other_channel = parent.with_untracked(|maybe_parent| {
+            maybe_parent
+                .as_ref()
+                .is_some_and(|parent| path != format!("/channel/{}", parent.channel))
+        });
+        if other_channel {
+            thread_ctx.close();
+        }
+    });
+    // only a different parent starts a new thread view, edits of the parent don't
+    let parent_uuid = create_memo(move |_| {
+        parent.with(|maybe_parent| maybe_parent.as_ref().map(|parent| parent.msg_uuid.clone()))
+    });
+
+    move || {
+        parent_uuid
+            .get()
+            .map(|parent_uuid| view! { <Thread parent_uuid/> })
+    }
+}
+
+#[component]
+fn Thread(parent_uuid: String) -> impl IntoView {
+    let thread_ctx = expect_context::<ThreadCtx>();
+    let ws_ctx = expect_context::<WebsocketCtx>();
+    let user_resource = expect_context::<Resource<(), Result<User, ServerFnError>>>();
+    let parent = thread_ctx.parent;
+    let role = thread_ctx.role;
+    let room_uuid = parent.with_untracked(|maybe_parent| {
+        maybe_parent
+            .as_ref()
+            .map(|parent| parent.channel.clone())
+            .unwrap_or_default()
+    });
+
+    // oldest first
+    let replies = create_rw_signal(Vec::<MsgResponse>::new());
+    let has_more = create_rw_signal(false);
+    let loading = create_rw_signal(false);
+    let load_replies = {
+        let room_uuid = room_uuid.clone();
+        let parent_uuid = parent_uuid.clone();
+        Callback::new(move |before: Option<MsgCursor>| {
+            loading.set(true);
+            let room_uuid = room_uuid.clone();
+            let parent_uuid = parent_uuid.clone();
+            spawn_local(async move {
+                match fetch_msg_page(room_uuid, Some(parent_uuid), before, None, PAGE_SIZE).await {
+                    Ok(page) => {
+                        has_more.set(page.has_more);
+                        replies.update(|vec| {
+                            let mut older = page.messages;
+                            older.retain(|msg| {
+                                !vec.iter().any(|known| known.msg_uuid == msg.msg_uuid)
+                            });
+                            older.append(vec);
+                            *vec = older;
+                        });
+                    }
+                    Err(err) => logging::warn!("unable to load replies: {}", err),
+                }
+                loading.set(false);
+            });
+        })
+    };
+    load_replies.call(None);
+    let handle_load_earlier = move |_: ev::MouseEvent| {
+        if let Some(oldest) = replies.with_untracked(|vec| vec.first().map(MsgCursor::from_msg)) {
+            load_replies.call(Some(oldest));
+        }
+    };
+
+    // --- keep the parent and the replies in sync with what the server pushes
+    {
+        let frame = ws_ctx.frame;
+        let parent_uuid = parent_uuid.clone();
+        create_effect(move |_| {
+            let update = |msg_uuid: &str, apply: &dyn Fn(&mut MsgResponse)| {
+                if msg_uuid == parent_uuid {
+                    parent.update(|maybe_parent| maybe_parent.iter_mut().for_each(apply));
+                } else {
+                    replies.update(|vec| {
+                        vec.iter_mut()
+                            .filter(|known| known.msg_uuid == msg_uuid)
+                            .for_each(apply)
+                    });
+                }
+            };
+            match frame.get() {
+                Some(ServerFrame::NewMessage { msg })
+                    if msg.parent.as_ref() == Some(&parent_uuid) =>
+                {
+                    update(&parent_uuid, &|parent: &mut MsgResponse| {
+                        parent.reply_count += 1
+                    });
+                    replies.update(|vec| {
+                        if !vec.iter().any(|known| known.msg_uuid == msg.msg_uuid) {
+                            vec.push(msg);
+                        }
+                    });
+                }
+                Some(ServerFrame::MessageEdited { msg } | ServerFrame::MessageDeleted { msg }) => {
+                    update(&msg.msg_uuid, &|known: &mut MsgResponse| {
+                        known.apply_change(msg.clone())
+                    });
+                }
+                Some(ServerFrame::ReactionsChanged {
+                    msg_uuid,
+                    reactions,
+                    ..
+                }) => {
+                    update(&msg_uuid, &|known: &mut MsgResponse| {
+                        known.reactions = reactions.clone()
+                    });
+                }
+                _ => {}
+            }
+        });
+    }
+
+    let reply_input = create_node_ref::<html::Input>();
+    let handle_keyup = {
+        let parent_uuid = parent_uuid.clone();
+        move |ev: ev::KeyboardEvent| {
+            if ev.key() != "Enter" {
+                return;
+            }
+            let input = reply_input.get().expect("input element doesn't exist");
+            let text = input.value().trim().to_string();
+            if !text.is_empty() {
+                ws_ctx.send_frame(&ClientFrame::PostMessage {
+                    room_uuid: room_uuid.clone(),
+                    text,
+                    parent: Some(parent_uuid.clone()),
+                });
+                input.set_value("");
+            }
+        }
+    };
+
+    let parent_msg = Signal::derive(move || parent.get().unwrap_or_default());
+
+    view! {
+        <div class="flex flex-col h-full w-full">
+            <div class="shrink-0 h-[50px] flex flex-row items-center justify-between px-3 border-b border-white/[.15]">
+                <p class="font-sans tracking-wider text-white">"Thread"</p>
+                <button
+                    on:click=move |_| thread_ctx.close()
+                    class="font-sans text-white/[.65] hover:text-white border-none bg-transparent"
+                >
+                    "✕"
+                </button>
+            </div>
+            <div class="flex flex-col grow px-2 overflow-y-scroll">
+                <ol class="flex flex-col border-b border-white/[.15] pb-2">
+                    <MessageBubble msg=parent_msg user_resource role in_thread=true/>
+                </ol>
+                <Show when=move || has_more.get()>
+                    <button
+                        on:click=handle_load_earlier
+                        disabled=move || loading.get()
+                        class="font-sans text-white/[.65] text-xs hover:text-white py-2 border-none bg-transparent"
+                    >
+                        "Load earlier replies"
+                    </button>
+                </Show>
+                <ol class="flex flex-col">
+                    <For
+                        each=move || replies.get()
+                        key=|msg_response| msg_response.msg_uuid.clone()
+                        children=move |msg_response| {
+                            let msg_uuid = msg_response.msg_uuid.clone();
+                            let msg = Signal::derive(move || {
+                                replies.with(|vec| {
+                                    vec.iter()
+                                        .find(|msg| msg.msg_uuid == msg_uuid)
+                                        .cloned()
+                                        .unwrap_or_else(|| msg_response.clone())
+                                })
+                            });
+                            view! { <MessageBubble msg user_resource role in_thread=true/> }
+                        }
+                    />
+                </ol>
+            </div>
+            <input
+                _ref=reply_input
+                on:keyup=handle_keyup
+                placeholder="Reply..."
+                class="shrink-0 m-2 rounded-md px-2 py-2 text-white font-sans text-sm bg-white/20 hover:bg-white/10 focus:bg-white/10 focus:outline-none border-0"
+            />
+        </div>
+    }
+}
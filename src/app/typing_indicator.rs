@@ -0,0 +1,92 @@
+use {
+    super::chat::WebsocketCtx,
+    crate::models::{frame_model::ClientFrame, user_model::User},
+    leptos::*,
+    std::time::Duration,
+};
+
+// the server forgets a typing user after 6s, so refresh well before that
+const TYPING_REFRESH: Duration = Duration::from_secs(3);
+// no keystroke for this long counts as having stopped typing
+const TYPING_IDLE: Duration = Duration::from_secs(4);
+
+// ---- throttles the typing frames sent while the user types in one room
+
+#[derive(Clone, Copy)]
+pub struct TypingNotifier {
+    room_uuid: StoredValue<String>,
+    // whether the server was told the user is typing
+    active: StoredValue<bool>,
+    // whether a start frame went out within the last `TYPING_REFRESH`
+    refreshed: StoredValue<bool>,
+    idle: StoredValue<Option<TimeoutHandle>>,
+}
+
+impl TypingNotifier {
+    pub fn new(room_uuid: String) -> Self {
+        Self {
+            room_uuid: store_value(room_uuid),
+            active: store_value(false),
+            refreshed: store_value(false),
+            idle: store_value(None),
+        }
+    }
+
+    pub fn keystroke(&self, ws_ctx: &WebsocketCtx) {
+        if let Some(handle) = self.idle.get_value() {
+            handle.clear();
+        }
+        if !self.active.get_value() || !self.refreshed.get_value() {
+            ws_ctx.send_frame(&ClientFrame::Typing {
+                room_uuid: self.room_uuid.get_value(),
+                active: true,
+            });
+            self.active.set_value(true);
+            self.refreshed.set_value(true);
+            let refreshed = self.refreshed;
+            let _ = set_timeout_with_handle(move || refreshed.set_value(false), TYPING_REFRESH);
+        }
+        let notifier = *self;
+        let ws_ctx = ws_ctx.clone();
+        let handle = set_timeout_with_handle(move || notifier.stop(&ws_ctx), TYPING_IDLE);
+        self.idle.set_value(handle.ok());
+    }
+
+    pub fn stop(&self, ws_ctx: &WebsocketCtx) {
+        if let Some(handle) = self.idle.get_value() {
+            handle.clear();
+            self.idle.set_value(None);
+        }
+        if self.active.get_value() {
+            ws_ctx.send_frame(&ClientFrame::Typing {
+                room_uuid: self.room_uuid.get_value(),
+                active: false,
+            });
+            self.active.set_value(false);
+            self.refreshed.set_value(false);
+        }
+    }
+}
+
+fn typing_text(users: &[User]) -> String {
+    match users {
+        [] => String::new(),
+        [one] => format!("{} is typing…", one.user_name),
+        [one, two] => format!("{} and {} are typing…", one.user_name, two.user_name),
+        [one, two, others @ ..] => format!(
+            "{}, {} and {} others are typing…",
+            one.user_name,
+            two.user_name,
+            others.len()
+        ),
+    }
+}
+
+#[component]
+pub fn TypingIndicator(typing_users: RwSignal<Vec<User>>) -> impl IntoView {
+    view! {
+        <p class="shrink-0 h-5 px-4 font-sans text-white/[.65] text-xs italic">
+            {move || typing_users.with(|users| typing_text(users))}
+        </p>
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::role_model::Permission;
 use http::status::StatusCode;
 use thiserror::Error;
 
@@ -34,10 +35,54 @@ pub enum ServerError {
     RemoveChannelError,
     #[error("User Does Not Exist")]
     UserDoesNotExist,
+    #[error("Message Does Not Exist")]
+    MessageDoesNotExist,
+    #[error("Only The Sender Can Modify The Message")]
+    NotMessageSender,
+    #[error("You Are Not A Member Of This Room")]
+    NotAMember,
+    #[error("Not Allowed To {0} In This Room")]
+    NotPermitted(Permission),
+    #[error("Only The Room Owner Can Change Roles")]
+    NotRoomOwner,
+    #[error("User Is Banned From The Room")]
+    BannedFromRoom,
+    #[error("Members Can Only Remove Those Below Their Own Role")]
+    CannotRemoveMember,
+    #[error("The Owner Can't Leave The Room, Delete It Instead")]
+    OwnerCannotLeave,
+    #[error("Invite Is Invalid, Expired Or Used Up")]
+    InvalidInvite,
+    #[error("Room Is Private, Joining Takes An Invite")]
+    NotPublic,
+    #[error("Room Is Archived")]
+    RoomArchived,
+    #[error("Invalid Room Settings: {0}")]
+    InvalidRoomSettings(&'static str),
+    #[error("Only The Participants Can See A Direct Conversation")]
+    PrivateConversation,
+    #[error("A Direct Conversation Needs Another User")]
+    DirectWithSelf,
+    #[error("A Group Has Between 3 And 8 Participants")]
+    GroupSize,
+    #[error("Only Group Conversations Can Take New Participants")]
+    NotAGroup,
     #[error("Email Has Been Taken")]
     EmailTaken,
+    #[error("Reaction Already Exists")]
+    ReactionExists,
+    #[error("Room Already Exists")]
+    RoomExists,
     #[error("Inter Process Communication Failed")]
     IPCFailed,
+    #[error("Missing Config: {0}")]
+    MissingConfig(&'static str),
+    #[error("Invalid Config: {0}")]
+    InvalidConfig(String),
+    #[error("Database Connection Timed Out: {0}")]
+    ConnectTimeout(String),
+    #[error("Migration Failed: {0}")]
+    MigrationFailed(String),
     #[error("Other: {0}")]
     Other(String),
 }
@@ -13,11 +13,11 @@ use {
     hey_leptos::{
         app, fileserv, messaging,
         models::user_model,
-        state::{self, auth, db, rooms_manager},
+        state::{self, auth, config, db, migrations, repository, rooms_manager},
     },
     leptos::*,
     leptos_axum::{generate_route_list, handle_server_fns_with_context, LeptosRoutes},
-    surrealdb::engine::remote::ws::Client as SurrealClient,
+    surrealdb::engine::any::Any as SurrealClient,
 };
 
 #[cfg(feature = "ssr")]
@@ -29,7 +29,7 @@ async fn server_fn_handler(
     handle_server_fns_with_context(
         move || {
             provide_context(auth_session.clone());
-            provide_context(app_state.pool.clone());
+            provide_context(app_state.repository.clone());
             provide_context(app_state.rooms_manager.clone());
         },
         request,
@@ -48,7 +48,7 @@ async fn leptos_routes_handler(
         app_state.routes.clone(),
         move || {
             provide_context(auth_session.clone());
-            provide_context(app_state.pool.clone());
+            provide_context(app_state.repository.clone());
             provide_context(app_state.rooms_manager.clone());
         },
         app::App,
@@ -59,9 +59,21 @@ async fn leptos_routes_handler(
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
-    let pool = db::Database::init()
+    let server_config = config::ServerConfig::load().map_err(|err| std::io::Error::other(err))?;
+    let pool = db::Database::init(&server_config.database)
         .await
         .map_err(|err| std::io::Error::other(err))?;
+    for migration in migrations::run(&pool)
+        .await
+        .map_err(|err| std::io::Error::other(err))?
+    {
+        logging::log!(
+            "applied migration {:04}_{}",
+            migration.version,
+            migration.name
+        );
+    }
+    let repository = repository::Repository::surreal(pool.clone());
     let rooms_manager = rooms_manager::RoomsManager::init();
     let conf = get_configuration(None)
         .await
@@ -82,7 +94,7 @@ async fn main() -> std::io::Result<()> {
 
     // --- AppState
     let app_state = state::AppState {
-        pool: pool.clone(),
+        repository: repository.clone(),
         leptos_options: leptos_options.clone(),
         routes: app_routes.clone(),
         rooms_manager: rooms_manager.clone(),
@@ -1,16 +1,18 @@
 #[cfg(feature = "ssr")]
 use {
     crate::{
+        error::ServerError,
         models::{
-            message_model::WsPayload,
+            frame_model::{ClientFrame, FrameError, ServerFrame, PROTOCOL_VERSION},
+            mention_model::Mention,
+            message_model::{MsgCursor, MsgData, MsgResponse},
+            presence_model::HEARTBEAT_TIMEOUT,
+            reaction_model::{is_valid_emoji, ReactionData, ReactionSummary},
+            read_marker_model::{ReadMarker, ReadReceipt},
+            role_model::Permission,
             user_model::{User, UserData},
         },
-        state::{
-            auth::AuthSession,
-            db::Database,
-            rooms_manager::{ChatRoom, RoomsManager},
-            AppState,
-        },
+        state::{auth::AuthSession, repository::Repository, rooms_manager::RoomsManager, AppState},
     },
     axum::{
         extract::{
@@ -20,8 +22,15 @@ use {
         http::StatusCode,
         response::IntoResponse,
     },
+    chrono::Utc,
     futures::{SinkExt, StreamExt},
-    tokio::sync::mpsc,
+    leptos::logging,
+    std::time::Duration,
+    tokio::{
+        sync::{broadcast::error::RecvError, mpsc},
+        time::timeout,
+    },
+    uuid::Uuid,
 };
 
 #[cfg(feature = "ssr")]
@@ -33,12 +42,16 @@ pub async fn ws_handler(
     // when we started the connection,
     // we need to sync with db whether we have Some created room(s) or None
     let rooms_manager = app_state.rooms_manager;
-    let pool = app_state.pool;
+    let repository = app_state.repository;
     let user = auth_session.current_user.ok_or(StatusCode::UNAUTHORIZED)?;
-    let user_data = UserData::get_from_uuid(&user.uuid, &pool)
+    let user_data = repository
+        .users
+        .get_user_by_uuid(&user.uuid)
         .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::UNAUTHORIZED)?;
-    Ok(ws.on_upgrade(|socket| handle_connection(socket, user, user_data, rooms_manager, pool)))
+    Ok(ws
+        .on_upgrade(|socket| handle_connection(socket, user, user_data, rooms_manager, repository)))
 }
 
 #[cfg(feature = "ssr")]
@@ -47,44 +60,53 @@ async fn handle_connection(
     user: User,
     user_data: UserData,
     rooms_manager: RoomsManager,
-    pool: Database,
+    repository: Repository,
 ) {
     let (mut ws_sender, mut ws_reader) = ws.split();
-    let (tx1, mut rx) = mpsc::unbounded_channel::<WsPayload>();
-    let tx2 = tx1.clone();
+    let (tx, mut rx) = mpsc::unbounded_channel::<ServerFrame>();
+    let reply = tx.clone();
     let mut ipc_receiver = rooms_manager.ipc_sender.subscribe();
-
-    let chatrooms = rooms_manager.chatrooms.clone();
-    let user_uuid = user.uuid.clone();
-    let user_id = user_uuid.clone();
-    let rm = rooms_manager.clone();
+    // every tab of the user has its own connection
+    let conn_id = Uuid::new_v4().as_simple().to_string();
 
     // --- Ensure on server restart, the rooms manager is synced with database
-    tokio::spawn(async move {
-        if !user_data.joined_channels.is_empty() {
-            let iter = user_data.joined_channels.iter().map(|room_uuid| async {
-                let room_data = rm.validate_uuid(room_uuid, &pool).await.unwrap();
-                let chatroom = ChatRoom::from_room_data(&room_data);
-                {
-                    let mut users = chatroom.users.write().unwrap();
-                    users.insert(user_id.clone(), Some(tx2.clone()));
-                }
-                let mut chatrooms = rm.chatrooms.write().unwrap();
-                chatrooms.insert(chatroom.uuid.clone(), chatroom);
-            });
-            futures::future::join_all(iter).await;
-        }
-    });
+    let (rm, repo, user_uuid, conn, sender) =
+        (&rooms_manager, &repository, &user.uuid, &conn_id, &tx);
+    let iter = user_data
+        .joined_channels
+        .iter()
+        .map(|room_uuid| async move {
+            match rm.validate_uuid(room_uuid, repo).await {
+                Ok(room_data) => rm.attach(&room_data, user_uuid, conn, sender.clone()),
+                Err(err) => logging::warn!("unable to attach to room {}: {}", room_uuid, err),
+            }
+        });
+    futures::future::join_all(iter).await;
+    rooms_manager.connect(&user.uuid, &conn_id);
 
     // --- Receive notification when user create or join to a new channel
-    tokio::spawn(async move {
-        while let Ok(chatroom) = ipc_receiver.recv().await {
-            let mut users = chatroom.users.write().unwrap();
-            users.insert(user_uuid.clone(), Some(tx1.clone()));
-            let mut chatrooms = chatrooms.write().unwrap();
-            chatrooms.insert(chatroom.uuid.clone(), chatroom.clone());
-        }
-    });
+    let ipc_task = {
+        let rooms_manager = rooms_manager.clone();
+        let user_uuid = user.uuid.clone();
+        let conn_id = conn_id.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            loop {
+                match ipc_receiver.recv().await {
+                    Ok(room_joined) if room_joined.user_uuid == user_uuid => {
+                        rooms_manager.attach(
+                            &room_joined.room_data,
+                            &user_uuid,
+                            &conn_id,
+                            tx.clone(),
+                        );
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    };
 
     // --- Send back the message to the client
     tokio::spawn(async move {
@@ -98,39 +120,299 @@ async fn handle_connection(
         let _ = ws_sender.close();
     });
 
-    // --- Receive message from the client
-    while let Some(Ok(ws_message)) = ws_reader.next().await {
-        let channel_payload = modify_msg(ws_message);
-        broadcast_msg(channel_payload, &rooms_manager).await;
+    // --- Receive message from the client, a connection silent for too long is dropped
+    let mut handshake_done = false;
+    loop {
+        let ws_message =
+            match timeout(Duration::from_secs(HEARTBEAT_TIMEOUT), ws_reader.next()).await {
+                Ok(Some(Ok(ws_message))) => ws_message,
+                _ => break,
+            };
+        let client_frame = match parse_frame(ws_message) {
+            Ok(Some(client_frame)) => client_frame,
+            Ok(None) => continue,
+            Err(error_frame) => {
+                let _ = reply.send(error_frame);
+                continue;
+            }
+        };
+
+        match client_frame {
+            ClientFrame::Hello { version } if version == PROTOCOL_VERSION => {
+                handshake_done = true;
+                let _ = reply.send(ServerFrame::Welcome {
+                    version: PROTOCOL_VERSION,
+                });
+            }
+            ClientFrame::Hello { version } => {
+                let _ = reply.send(ServerFrame::error(
+                    FrameError::UnsupportedVersion,
+                    format!(
+                        "protocol version {} is not supported, expected {}",
+                        version, PROTOCOL_VERSION
+                    ),
+                ));
+                break;
+            }
+            _ if !handshake_done => {
+                let _ = reply.send(ServerFrame::error(
+                    FrameError::HandshakeRequired,
+                    "send a hello frame first",
+                ));
+            }
+            ClientFrame::PostMessage {
+                room_uuid,
+                text,
+                parent,
+            } => {
+                match post_msg(&room_uuid, text, parent, &user, &repository, &rooms_manager).await {
+                    Ok(msg) => {
+                        rooms_manager.stop_typing(&room_uuid, &user);
+                        rooms_manager.notify_mentions(&msg, &msg.mentions);
+                        rooms_manager.broadcast(&room_uuid, ServerFrame::NewMessage { msg });
+                    }
+                    Err(error_frame) => {
+                        let _ = reply.send(error_frame);
+                    }
+                }
+            }
+            ClientFrame::ToggleReaction { msg_uuid, emoji } => {
+                match toggle_reaction(&msg_uuid, emoji, &user, &repository, &rooms_manager).await {
+                    Ok((room_uuid, server_frame)) => {
+                        rooms_manager.broadcast(&room_uuid, server_frame)
+                    }
+                    Err(error_frame) => {
+                        let _ = reply.send(error_frame);
+                    }
+                }
+            }
+            ClientFrame::Typing { room_uuid, active } if active => {
+                let is_member = rooms_manager
+                    .validate_uuid(&room_uuid, &repository)
+                    .await
+                    .is_ok_and(|room_data| room_data.users.contains_key(&user.uuid));
+                if is_member {
+                    rooms_manager.start_typing(&room_uuid, &user);
+                } else {
+                    let _ = reply.send(ServerFrame::error(
+                        FrameError::NotAMember,
+                        "you are not a member of this room",
+                    ));
+                }
+            }
+            ClientFrame::Typing { room_uuid, .. } => rooms_manager.stop_typing(&room_uuid, &user),
+            ClientFrame::Heartbeat { idle } => rooms_manager.heartbeat(&user.uuid, &conn_id, idle),
+            ClientFrame::Ack { room_uuid, cursor } => {
+                if let Err(error_frame) =
+                    ack(&room_uuid, cursor, &user, &repository, &rooms_manager).await
+                {
+                    let _ = reply.send(error_frame);
+                }
+            }
+        }
     }
+
+    // --- the connection is gone, nobody should see this user typing or online anymore
+    ipc_task.abort();
+    rooms_manager.stop_all_typing(&user);
+    rooms_manager.disconnect(&user.uuid, &conn_id);
 }
 
 #[cfg(feature = "ssr")]
-fn modify_msg(ws_message: Message) -> WsPayload {
+fn parse_frame(ws_message: Message) -> Result<Option<ClientFrame>, ServerFrame> {
     // on page refresh, the browser will send ws Close message
     // so i need to handle this to avoid error
-    let recv_payload = match ws_message {
-        Message::Text(text) => {
-            serde_json::from_str::<WsPayload>(&text).unwrap_or_else(|_| WsPayload::default())
-        }
-        _ => WsPayload::default(),
-    };
-    let modified_response = match recv_payload.op_code {
-        1 => WsPayload::new(11, recv_payload.message.clone()),
-        _ => WsPayload::default(),
+    let parsed = match ws_message {
+        Message::Text(text) => serde_json::from_str::<ClientFrame>(&text),
+        Message::Binary(bytes) => serde_json::from_slice::<ClientFrame>(&bytes),
+        _ => return Ok(None),
     };
-    modified_response
+    parsed
+        .map(Some)
+        .map_err(|err| ServerFrame::error(FrameError::MalformedFrame, err.to_string()))
 }
 
+// --- Persist the posted message first, the room is only notified once the write is committed
 #[cfg(feature = "ssr")]
-async fn broadcast_msg(channel_payload: WsPayload, rooms_manager: &RoomsManager) {
-    let chatrooms = rooms_manager.chatrooms.read().unwrap();
-    if let Some(chatroom) = chatrooms.get(&channel_payload.message) {
-        let users = chatroom.users.read().unwrap();
-        for (_, channel) in users.iter() {
-            if let Some(tx1) = channel {
-                let _ = tx1.send(channel_payload.clone());
-            }
+async fn post_msg(
+    room_uuid: &str,
+    text: String,
+    parent: Option<String>,
+    user: &User,
+    repository: &Repository,
+    rooms_manager: &RoomsManager,
+) -> Result<MsgResponse, ServerFrame> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(ServerFrame::error(FrameError::Rejected, "message is empty"));
+    }
+    let room_data = rooms_manager
+        .validate_uuid(room_uuid, repository)
+        .await
+        .map_err(|err| ServerFrame::error(FrameError::Rejected, err.to_string()))?;
+    if !room_data.users.contains_key(&user.uuid) {
+        return Err(ServerFrame::error(
+            FrameError::NotAMember,
+            "you are not a member of this room",
+        ));
+    }
+    if room_data.archived {
+        return Err(ServerFrame::error(
+            FrameError::Rejected,
+            ServerError::RoomArchived.to_string(),
+        ));
+    }
+    room_data
+        .require(&user.uuid, Permission::Post)
+        .map_err(|err| ServerFrame::error(FrameError::Rejected, err.to_string()))?;
+
+    // --- replies always hang off the top level message, threads don't nest
+    let parent = match parent {
+        Some(parent_uuid) => {
+            let parent_data = repository
+                .messages
+                .get_msg(&parent_uuid)
+                .await
+                .map_err(|err| ServerFrame::error(FrameError::Rejected, err.to_string()))?
+                .filter(|parent_data| parent_data.channel == room_data.room_uuid)
+                .ok_or_else(|| {
+                    ServerFrame::error(FrameError::Rejected, "the replied message doesn't exist")
+                })?;
+            Some(parent_data.parent.unwrap_or(parent_data.msg_uuid))
         }
+        None => None,
+    };
+
+    let mentions = Mention::parse(&text, room_data.users.values());
+    let mut msg_data = MsgData::new(
+        room_data.room_uuid,
+        user.uuid.clone(),
+        text,
+        parent,
+        Utc::now(),
+    );
+    msg_data.mentions = mentions;
+    repository
+        .messages
+        .insert_msg(&msg_data)
+        .await
+        .map_err(|err| ServerFrame::error(FrameError::Rejected, err.to_string()))?;
+    Ok(MsgResponse::new(&msg_data, Some(user.clone())))
+}
+
+// --- Returns the room to notify together with the fresh reactions of the message
+#[cfg(feature = "ssr")]
+async fn toggle_reaction(
+    msg_uuid: &str,
+    emoji: String,
+    user: &User,
+    repository: &Repository,
+    rooms_manager: &RoomsManager,
+) -> Result<(String, ServerFrame), ServerFrame> {
+    let rejected = |err: ServerError| ServerFrame::error(FrameError::Rejected, err.to_string());
+    if !is_valid_emoji(&emoji) {
+        return Err(ServerFrame::error(
+            FrameError::Rejected,
+            "not a valid emoji",
+        ));
+    }
+    let msg_data = repository
+        .messages
+        .get_msg(msg_uuid)
+        .await
+        .map_err(rejected)?
+        .filter(|msg_data| !msg_data.is_deleted())
+        .ok_or_else(|| rejected(ServerError::MessageDoesNotExist))?;
+    let room_data = rooms_manager
+        .validate_uuid(&msg_data.channel, repository)
+        .await
+        .map_err(rejected)?;
+    if !room_data.users.contains_key(&user.uuid) {
+        return Err(ServerFrame::error(
+            FrameError::NotAMember,
+            "you are not a member of this room",
+        ));
+    }
+
+    let msg_uuids = [msg_data.msg_uuid.clone()];
+    let already_reacted = repository
+        .reactions
+        .get_reactions(&msg_uuids)
+        .await
+        .map_err(rejected)?
+        .iter()
+        .any(|reaction| reaction.user_uuid == user.uuid && reaction.emoji == emoji);
+    if already_reacted {
+        repository
+            .reactions
+            .delete_reaction(&msg_data.msg_uuid, &user.uuid, &emoji)
+            .await
+            .map_err(rejected)?;
+    } else {
+        let reaction = ReactionData::new(
+            msg_data.msg_uuid.clone(),
+            user.uuid.clone(),
+            emoji,
+            Utc::now(),
+        );
+        repository
+            .reactions
+            .insert_reaction(&reaction)
+            .await
+            .map_err(rejected)?;
+    }
+
+    let reactions = ReactionSummary::load(&msg_uuids, repository)
+        .await
+        .map_err(rejected)?
+        .remove(&msg_data.msg_uuid)
+        .unwrap_or_default();
+    Ok((
+        msg_data.channel.clone(),
+        ServerFrame::ReactionsChanged {
+            room_uuid: msg_data.channel,
+            msg_uuid: msg_data.msg_uuid,
+            reactions,
+        },
+    ))
+}
+
+// --- Moves the read marker, the other members only hear about it when the room shows receipts
+#[cfg(feature = "ssr")]
+async fn ack(
+    room_uuid: &str,
+    cursor: MsgCursor,
+    user: &User,
+    repository: &Repository,
+    rooms_manager: &RoomsManager,
+) -> Result<(), ServerFrame> {
+    let rejected = |err: ServerError| ServerFrame::error(FrameError::Rejected, err.to_string());
+    let room_data = rooms_manager
+        .validate_uuid(room_uuid, repository)
+        .await
+        .map_err(rejected)?;
+    if !room_data.users.contains_key(&user.uuid) {
+        return Err(ServerFrame::error(
+            FrameError::NotAMember,
+            "you are not a member of this room",
+        ));
+    }
+
+    let moved = ReadMarker::advance(&user.uuid, room_uuid, &cursor.msg_uuid, repository)
+        .await
+        .map_err(rejected)?;
+    if let Some(cursor) = moved.filter(|_| room_data.read_receipts) {
+        rooms_manager.broadcast_except(
+            room_uuid,
+            &user.uuid,
+            ServerFrame::ReadReceipt {
+                room_uuid: room_uuid.to_string(),
+                receipt: ReadReceipt {
+                    user: user.clone(),
+                    cursor,
+                },
+            },
+        );
     }
+    Ok(())
 }
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ---- a public room as the directory lists it
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PublicRoom {
+    pub room_uuid: String,
+    pub room_name: String,
+    pub description: String,
+    pub member_count: usize,
+    // the latest message, or the creation of the room when nobody wrote yet
+    pub last_activity: DateTime<Utc>,
+    pub joined: bool,
+}
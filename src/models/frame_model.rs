@@ -0,0 +1,144 @@
+use super::{
+    message_model::{MsgCursor, MsgResponse},
+    presence_model::PresenceStatus,
+    reaction_model::ReactionSummary,
+    read_marker_model::ReadReceipt,
+    role_model::Role,
+    room_settings_model::RoomSettings,
+    user_model::User,
+};
+use serde::{Deserialize, Serialize};
+
+// bump this whenever a frame changes shape, the client announces the version
+// it speaks in `ClientFrame::Hello` and the server refuses anything else
+pub const PROTOCOL_VERSION: u16 = 14;
+
+// ---- frames sent by the browser
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ClientFrame {
+    Hello {
+        version: u16,
+    },
+    // `parent` is set when replying in a thread
+    PostMessage {
+        room_uuid: String,
+        text: String,
+        #[serde(default)]
+        parent: Option<String>,
+    },
+    // adds the reaction, or removes it when the user already reacted with that emoji
+    ToggleReaction {
+        msg_uuid: String,
+        emoji: String,
+    },
+    // sent when the user starts typing and repeated while they keep typing,
+    // `active: false` once they stop
+    Typing {
+        room_uuid: String,
+        active: bool,
+    },
+    // keeps the connection alive, sent every `HEARTBEAT_INTERVAL` and whenever idleness changes
+    Heartbeat {
+        idle: bool,
+    },
+    // the user has read the room up to `cursor`
+    Ack {
+        room_uuid: String,
+        cursor: MsgCursor,
+    },
+}
+
+// ---- frames sent by the server
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ServerFrame {
+    Welcome {
+        version: u16,
+    },
+    NewMessage {
+        msg: MsgResponse,
+    },
+    MessageEdited {
+        msg: MsgResponse,
+    },
+    // carries the tombstone that replaces the message
+    MessageDeleted {
+        msg: MsgResponse,
+    },
+    ReactionsChanged {
+        room_uuid: String,
+        msg_uuid: String,
+        reactions: Vec<ReactionSummary>,
+    },
+    Typing {
+        room_uuid: String,
+        user: User,
+        active: bool,
+    },
+    Presence {
+        user_uuid: String,
+        status: PresenceStatus,
+    },
+    ReadReceipt {
+        room_uuid: String,
+        receipt: ReadReceipt,
+    },
+    ReadReceiptsChanged {
+        room_uuid: String,
+        enabled: bool,
+    },
+    // sent only to the mentioned user, next to the `NewMessage` the whole room gets
+    Mention {
+        msg: MsgResponse,
+    },
+    // everyone now in a group conversation, after someone added participants
+    ParticipantsChanged {
+        room_uuid: String,
+        users: Vec<User>,
+    },
+    RoleChanged {
+        room_uuid: String,
+        user_uuid: String,
+        role: Role,
+    },
+    // the member left or was kicked, the removed user gets it too before losing the room
+    MemberRemoved {
+        room_uuid: String,
+        user_uuid: String,
+        banned: bool,
+    },
+    RoomUpdated {
+        room_uuid: String,
+        settings: RoomSettings,
+    },
+    // sent to the members right before the room goes away
+    RoomDeleted {
+        room_uuid: String,
+    },
+    Error {
+        code: FrameError,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameError {
+    MalformedFrame,
+    UnsupportedVersion,
+    HandshakeRequired,
+    NotAMember,
+    Rejected,
+}
+
+impl ServerFrame {
+    pub fn error(code: FrameError, message: impl Into<String>) -> Self {
+        Self::Error {
+            code,
+            message: message.into(),
+        }
+    }
+}
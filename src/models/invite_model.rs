@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ---- a shareable code that lets whoever holds it join a room
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Invite {
+    pub code: String,
+    pub room_uuid: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    // never expires when none
+    pub expires_at: Option<DateTime<Utc>>,
+    // unlimited when none
+    pub max_uses: Option<u32>,
+    pub uses: u32,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Invite {
+    pub fn new(
+        room_uuid: String,
+        created_by: String,
+        created_at: DateTime<Utc>,
+        expires_at: Option<DateTime<Utc>>,
+        max_uses: Option<u32>,
+    ) -> Self {
+        // 60 random bits, the 13th hex digit is the uuid version, short enough to read out loud
+        let code = uuid::Uuid::new_v4().simple().to_string()[..16].to_string();
+        Self {
+            code,
+            room_uuid,
+            created_by,
+            created_at,
+            expires_at,
+            max_uses,
+            uses: 0,
+            revoked_at: None,
+        }
+    }
+
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none()
+            && self.expires_at.is_none_or(|expires_at| now < expires_at)
+            && self.max_uses.is_none_or(|max_uses| self.uses < max_uses)
+    }
+
+    // accepts the bare code as well as a whole `/invite/<code>` link
+    pub fn code_from(input: &str) -> &str {
+        let input = input.trim().trim_end_matches('/');
+        input.rsplit('/').next().unwrap_or(input)
+    }
+}
+
+// what someone holding the code gets to see before joining
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InvitePreview {
+    pub code: String,
+    pub room_uuid: String,
+    pub room_name: String,
+    pub member_count: usize,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub joined: bool,
+}
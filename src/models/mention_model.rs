@@ -0,0 +1,167 @@
+use super::user_model::User;
+use serde::{Deserialize, Serialize};
+
+// a `@user_name` in a message resolved against the room members when it was posted
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Mention {
+    pub user_uuid: String,
+    pub user_name: String,
+}
+
+impl Mention {
+    // every member mentioned in `text`, in order of first appearance,
+    // the longest name wins when several members share a prefix
+    pub fn parse<'a>(text: &str, members: impl IntoIterator<Item = &'a User>) -> Vec<Self> {
+        let members = members.into_iter().collect::<Vec<&User>>();
+        let mut vec_mention: Vec<Self> = Vec::new();
+        for idx in mention_starts(text) {
+            let Some(user) =
+                longest_match(&text[idx + 1..], members.iter().map(|user| &user.user_name))
+                    .and_then(|name| members.iter().find(|user| &user.user_name == name))
+            else {
+                continue;
+            };
+            if !vec_mention
+                .iter()
+                .any(|mention| mention.user_uuid == user.uuid)
+            {
+                vec_mention.push(Self {
+                    user_uuid: user.uuid.clone(),
+                    user_name: user.user_name.clone(),
+                });
+            }
+        }
+        vec_mention
+    }
+}
+
+// ---- splitting a message into plain text and mentions, for rendering
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextSegment {
+    Plain(String),
+    Mention(Mention),
+}
+
+pub fn segments(text: &str, mentions: &[Mention]) -> Vec<TextSegment> {
+    let mut vec_segment = Vec::new();
+    let mut plain_start = 0;
+    let mut resume_at = 0;
+    for idx in mention_starts(text) {
+        if idx < resume_at {
+            continue;
+        }
+        let Some(mention) = longest_match(&text[idx + 1..], mentions.iter().map(|m| &m.user_name))
+            .and_then(|name| mentions.iter().find(|m| &m.user_name == name))
+        else {
+            continue;
+        };
+        if plain_start < idx {
+            vec_segment.push(TextSegment::Plain(text[plain_start..idx].to_string()));
+        }
+        resume_at = idx + 1 + mention.user_name.len();
+        plain_start = resume_at;
+        vec_segment.push(TextSegment::Mention(mention.clone()));
+    }
+    if plain_start < text.len() {
+        vec_segment.push(TextSegment::Plain(text[plain_start..].to_string()));
+    }
+    vec_segment
+}
+
+// byte offsets of the `@` that open a mention, at the start of the text or after whitespace
+fn mention_starts(text: &str) -> impl Iterator<Item = usize> + '_ {
+    text.match_indices('@').map(|(idx, _)| idx).filter(|&idx| {
+        text[..idx]
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace)
+    })
+}
+
+// the longest name `rest` starts with that isn't followed by more of a name
+fn longest_match<'a>(rest: &str, names: impl Iterator<Item = &'a String>) -> Option<&'a String> {
+    names
+        .filter(|name| {
+            !name.is_empty()
+                && rest.starts_with(name.as_str())
+                && rest[name.len()..]
+                    .chars()
+                    .next()
+                    .is_none_or(|c| !c.is_alphanumeric() && c != '_')
+        })
+        .max_by_key(|name| name.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(uuid: &str, user_name: &str) -> User {
+        User {
+            uuid: uuid.to_string(),
+            user_name: user_name.to_string(),
+            ..User::default()
+        }
+    }
+
+    fn mention(user: &User) -> Mention {
+        Mention {
+            user_uuid: user.uuid.clone(),
+            user_name: user.user_name.clone(),
+        }
+    }
+
+    #[test]
+    fn parse_keeps_the_first_appearance_of_each_member() {
+        let (ann, bob) = (member("1", "ann"), member("2", "bob"));
+        let vec_mention = Mention::parse("hi @bob and @ann, @bob again", [&ann, &bob]);
+        assert_eq!(vec_mention, [mention(&bob), mention(&ann)]);
+    }
+
+    #[test]
+    fn parse_prefers_the_longest_name_and_stops_at_punctuation() {
+        let (ann, anna) = (member("1", "ann"), member("2", "anna"));
+        let members = [&ann, &anna];
+        assert_eq!(Mention::parse("@anna hi", members), [mention(&anna)]);
+        assert_eq!(Mention::parse("thanks @ann.", members), [mention(&ann)]);
+        assert!(Mention::parse("(@ann)", members).is_empty());
+        assert!(Mention::parse("@annie", members).is_empty());
+    }
+
+    #[test]
+    fn parse_skips_unknown_names_and_addresses() {
+        let bob = member("2", "bob");
+        assert!(Mention::parse("@carol hi", [&bob]).is_empty());
+        assert!(Mention::parse("mail@bob", [&bob]).is_empty());
+    }
+
+    #[test]
+    fn segments_split_at_byte_offsets() {
+        let (ann, bob) = (member("1", "ann"), member("2", "bob"));
+        let mentions = [mention(&ann), mention(&bob)];
+        assert_eq!(
+            segments("héllo @ann, see @bob!", &mentions),
+            [
+                TextSegment::Plain("héllo ".to_string()),
+                TextSegment::Mention(mention(&ann)),
+                TextSegment::Plain(", see ".to_string()),
+                TextSegment::Mention(mention(&bob)),
+                TextSegment::Plain("!".to_string()),
+            ]
+        );
+        assert_eq!(
+            segments("@bob", &mentions),
+            [TextSegment::Mention(mention(&bob))]
+        );
+    }
+
+    #[test]
+    fn segments_leave_unknown_names_plain() {
+        let mentions = [mention(&member("1", "ann"))];
+        assert_eq!(
+            segments("@carol and @annie", &mentions),
+            [TextSegment::Plain("@carol and @annie".to_string())]
+        );
+    }
+}
@@ -1,22 +1,10 @@
 use std::cmp::Ordering;
 
-use super::user_model::User;
+use super::{mention_model::Mention, reaction_model::ReactionSummary, user_model::User};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct WsPayload {
-    pub op_code: u8,
-    pub message: String,
-}
-
-impl WsPayload {
-    pub fn new(op_code: u8, message: String) -> Self {
-        Self { op_code, message }
-    }
-}
-
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct MsgData {
     pub msg_uuid: String,
@@ -24,6 +12,18 @@ pub struct MsgData {
     pub channel: String,
     pub message: String,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub edited_at: Option<DateTime<Utc>>,
+    // a deleted message stays as a tombstone so cursors and threads keep pointing at it
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub deleted_by: Option<String>,
+    // the message this one replies to, threads are a single level deep
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub mentions: Vec<Mention>,
 }
 
 impl MsgData {
@@ -31,6 +31,7 @@ impl MsgData {
         channel: String,
         msg_sender: String,
         message: String,
+        parent: Option<String>,
         created_at: DateTime<Utc>,
     ) -> Self {
         let msg_uuid = Uuid::new_v4().as_simple().to_string();
@@ -40,6 +41,41 @@ impl MsgData {
             channel,
             message,
             created_at,
+            edited_at: None,
+            deleted_at: None,
+            deleted_by: None,
+            parent,
+            mentions: Vec::new(),
+        }
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    pub fn tombstone(&mut self, deleted_by: String, deleted_at: DateTime<Utc>) {
+        self.message.clear();
+        self.mentions.clear();
+        self.deleted_at = Some(deleted_at);
+        self.deleted_by = Some(deleted_by);
+    }
+}
+
+// ---- the text a message had before an edit replaced it
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MsgRevision {
+    pub msg_uuid: String,
+    pub message: String,
+    pub edited_at: DateTime<Utc>,
+}
+
+impl MsgRevision {
+    pub fn new(msg_data: &MsgData, edited_at: DateTime<Utc>) -> Self {
+        Self {
+            msg_uuid: msg_data.msg_uuid.clone(),
+            message: msg_data.message.clone(),
+            edited_at,
         }
     }
 }
@@ -51,17 +87,30 @@ pub struct MsgResponse {
     pub channel: String,
     pub message: String,
     pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by: Option<String>,
+    #[serde(default)]
+    pub reactions: Vec<ReactionSummary>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub reply_count: usize,
+    #[serde(default)]
+    pub mentions: Vec<Mention>,
 }
 
+// a message is identified by where it sits, the same key the pagination cursor uses,
+// so an edited copy still equals the original
 impl PartialEq for MsgResponse {
     fn eq(&self, other: &Self) -> bool {
-        self.msg_uuid == other.msg_uuid
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl Ord for MsgResponse {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.created_at.cmp(&other.created_at)
+        (self.created_at, &self.msg_uuid).cmp(&(other.created_at, &other.msg_uuid))
     }
 }
 
@@ -71,145 +120,149 @@ impl PartialOrd for MsgResponse {
     }
 }
 
-#[cfg(feature = "ssr")]
-use {crate::state::db::Database, futures::future::join_all};
+// ---- pagination, a cursor points at a message and pages never include it
 
-#[cfg(feature = "ssr")]
-impl MsgResponse {
-    async fn from_msg_data(msg_data: &MsgData, pool: &Database) -> Self {
-        let query_user = pool
-            .client
-            .query("SELECT * FROM type::table($table) WHERE uuid = $uuid")
-            .bind(("table", "user_data"))
-            .bind(("uuid", &msg_data.msg_sender))
-            .await;
-
-        let maybe_user = match query_user {
-            Ok(mut maybe_user) => maybe_user.take::<Option<User>>(0).unwrap_or(None),
-            Err(_) => None,
-        };
+pub const MAX_PAGE_SIZE: usize = 100;
 
+// ordered by `created_at` first, then by `msg_uuid`, the same order pages walk in
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MsgCursor {
+    pub created_at: DateTime<Utc>,
+    pub msg_uuid: String,
+}
+
+impl MsgCursor {
+    pub fn from_msg(msg: &MsgResponse) -> Self {
+        Self {
+            created_at: msg.created_at,
+            msg_uuid: msg.msg_uuid.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct MsgPage {
+    // always sorted from the oldest to the newest
+    pub messages: Vec<MsgResponse>,
+    // whether there are more messages past this page in the direction it was fetched
+    pub has_more: bool,
+}
+
+impl MsgResponse {
+    pub fn new(msg_data: &MsgData, msg_sender: Option<User>) -> Self {
         Self {
             msg_uuid: msg_data.msg_uuid.clone(),
-            msg_sender: maybe_user,
+            msg_sender,
             channel: msg_data.channel.clone(),
             message: msg_data.message.clone(),
-            created_at: msg_data.created_at.clone(),
+            created_at: msg_data.created_at,
+            edited_at: msg_data.edited_at,
+            deleted_at: msg_data.deleted_at,
+            deleted_by: msg_data.deleted_by.clone(),
+            reactions: Vec::new(),
+            parent: msg_data.parent.clone(),
+            reply_count: 0,
+            mentions: msg_data.mentions.clone(),
         }
     }
 
-    pub async fn get_all_msg(
-        room_uuid: &str,
-        pool: &Database,
-    ) -> Result<Vec<Self>, surrealdb::Error> {
-        match pool
-            .client
-            .query("SELECT * FROM message WHERE channel = $channel")
-            .bind(("channel", room_uuid))
-            .await
-        {
-            Ok(mut query_result) => {
-                let response = query_result.take::<Vec<MsgData>>(0);
-                let result = if let Ok(vec_msg) = response {
-                    let future_vec = vec_msg
-                        .iter()
-                        .map(|msg| async { Self::from_msg_data(msg, &pool).await });
-                    join_all(future_vec).await
-                } else {
-                    Vec::new()
-                };
-
-                Ok(result)
-            }
-            Err(err) => Err(err),
-        }
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
     }
 
-    pub async fn get_limited_msg(
-        room_uuid: &str,
-        pool: &Database,
-        start: u32,
-    ) -> Result<Vec<Self>, surrealdb::Error> {
-        match pool
-            .client
-            .query("SELECT * FROM message WHERE channel = $channel LIMIT 10 START $start")
-            .bind(("channel", room_uuid))
-            .bind(("start", start))
-            .await
-        {
-            Ok(mut query_result) => {
-                let response = query_result.take::<Vec<MsgData>>(0);
-                let result = if let Ok(vec_msg) = response {
-                    let future_vec = vec_msg
-                        .iter()
-                        .map(|msg| async { Self::from_msg_data(msg, &pool).await });
-                    join_all(future_vec).await
-                } else {
-                    Vec::new()
-                };
-
-                Ok(result)
-            }
-            Err(err) => Err(err),
-        }
+    pub fn mentions_user(&self, user_uuid: &str) -> bool {
+        self.mentions
+            .iter()
+            .any(|mention| mention.user_uuid == user_uuid)
+    }
+
+    // applies an edit or a deletion pushed by the server, those frames carry
+    // neither the reactions nor the reply count
+    pub fn apply_change(&mut self, changed: MsgResponse) {
+        let reactions = if changed.is_deleted() {
+            Vec::new()
+        } else {
+            std::mem::take(&mut self.reactions)
+        };
+        *self = Self {
+            reactions,
+            reply_count: self.reply_count,
+            ..changed
+        };
     }
 }
 
 #[cfg(feature = "ssr")]
-impl MsgData {
-    pub async fn into_msg_response(&self, pool: &Database) -> MsgResponse {
-        MsgResponse::from_msg_data(self, pool).await
-    }
+use {
+    crate::{
+        error::ServerError,
+        state::repository::{Repository, UserRepository},
+    },
+    futures::future::join_all,
+};
 
-    pub async fn insert_into_db(&self, pool: &Database) -> Result<(), surrealdb::Error> {
-        match pool
-            .client
-            .create::<Option<Self>>(("message", self.msg_uuid.clone()))
-            .content(self)
+#[cfg(feature = "ssr")]
+impl MsgResponse {
+    async fn from_msg_data(msg_data: &MsgData, users: &dyn UserRepository) -> Self {
+        let maybe_user = users
+            .get_user_by_uuid(&msg_data.msg_sender)
             .await
-        {
-            Ok(_) => Ok(()),
-            Err(err) => Err(err),
-        }
+            .unwrap_or(None)
+            .map(|user_data| User::from_user_data(&user_data));
+
+        Self::new(msg_data, maybe_user)
     }
 
-    async fn _get_from_uuid(msg_uuid: &str, pool: &Database) -> Option<Self> {
-        match pool
-            .client
-            .query("SELECT * FROM type::table($table) WHERE msg_uuid = $msg_uuid")
-            .bind(("table", "message"))
-            .bind(("msg_uuid", msg_uuid))
-            .await
-        {
-            Ok(mut query_result) => query_result.take::<Option<Self>>(0).unwrap_or(None),
-            Err(_) => None,
-        }
+    // top level messages of the room when `parent` is none, the replies to `parent` otherwise
+    pub async fn get_page(
+        room_uuid: &str,
+        parent: Option<&str>,
+        before: Option<&MsgCursor>,
+        after: Option<&MsgCursor>,
+        limit: usize,
+        repository: &Repository,
+    ) -> Result<MsgPage, ServerError> {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        // one extra row tells whether another page exists
+        let mut vec_msg = repository
+            .messages
+            .get_msg_page(room_uuid, parent, before, after, limit + 1)
+            .await?;
+        let has_more = vec_msg.len() > limit;
+        vec_msg.truncate(limit);
+        vec_msg.sort_by(|a, b| (a.created_at, &a.msg_uuid).cmp(&(b.created_at, &b.msg_uuid)));
+
+        let future_vec = vec_msg
+            .iter()
+            .map(|msg| Self::from_msg_data(msg, repository.users.as_ref()));
+        Ok(MsgPage {
+            messages: Self::with_details(join_all(future_vec).await, repository).await?,
+            has_more,
+        })
     }
 
-    async fn _get_all_msg(room_uuid: &str, pool: &Database) -> Result<Vec<Self>, surrealdb::Error> {
-        match pool
-            .client
-            .query("SELECT * FROM type::table($table) WHERE channel = $channel")
-            .bind(("table", "message"))
-            .bind(("channel", room_uuid))
-            .await
-        {
-            Ok(mut query_result) => Ok(query_result.take::<Vec<Self>>(0).unwrap_or(Vec::new())),
-            Err(err) => Err(err),
+    // fills in what lives outside the message row, reactions and reply counts
+    async fn with_details(
+        mut vec_msg: Vec<Self>,
+        repository: &Repository,
+    ) -> Result<Vec<Self>, ServerError> {
+        let msg_uuids = vec_msg
+            .iter()
+            .map(|msg| msg.msg_uuid.clone())
+            .collect::<Vec<String>>();
+        let mut summaries = ReactionSummary::load(&msg_uuids, repository).await?;
+        let reply_counts = repository.messages.count_replies(&msg_uuids).await?;
+        for msg in vec_msg.iter_mut() {
+            msg.reactions = summaries.remove(&msg.msg_uuid).unwrap_or_default();
+            msg.reply_count = reply_counts.get(&msg.msg_uuid).copied().unwrap_or_default();
         }
+        Ok(vec_msg)
     }
+}
 
-    async fn _stream_msg(
-        room_uuid: &str,
-        pool: &Database,
-    ) -> Result<surrealdb::method::QueryStream<surrealdb::Notification<Self>>, surrealdb::Error>
-    {
-        let mut response = pool
-            .client
-            .query("LIVE SELECT * FROM message WHERE channel = $channel")
-            .bind(("channel", room_uuid))
-            .await?;
-
-        response.stream::<surrealdb::Notification<Self>>(0)
+#[cfg(feature = "ssr")]
+impl MsgData {
+    pub async fn into_msg_response(&self, users: &dyn UserRepository) -> MsgResponse {
+        MsgResponse::from_msg_data(self, users).await
     }
 }
@@ -1,2 +1,11 @@
+pub mod directory_model;
+pub mod frame_model;
+pub mod invite_model;
+pub mod mention_model;
 pub mod message_model;
+pub mod presence_model;
+pub mod reaction_model;
+pub mod read_marker_model;
+pub mod role_model;
+pub mod room_settings_model;
 pub mod user_model;
@@ -0,0 +1,26 @@
+use super::{role_model::Role, user_model::User};
+use serde::{Deserialize, Serialize};
+
+// how often the client sends a heartbeat, in seconds
+pub const HEARTBEAT_INTERVAL: u64 = 30;
+// a connection that sent nothing for this long is considered dead, in seconds
+pub const HEARTBEAT_TIMEOUT: u64 = 90;
+// no user activity for this long makes the client report itself idle, in seconds
+pub const IDLE_AFTER: u64 = 300;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceStatus {
+    Online,
+    Idle,
+    #[default]
+    Offline,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MemberPresence {
+    pub user: User,
+    pub status: PresenceStatus,
+    #[serde(default)]
+    pub role: Role,
+}
@@ -0,0 +1,133 @@
+use super::user_model::User;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// offered in the reaction picker, any other emoji is accepted as well
+pub const QUICK_REACTIONS: &[&str] = &["👍", "❤️", "😂", "🎉", "👀", "🙏"];
+
+// an emoji can be a sequence of several code points (skin tones, flags, zwj sequences)
+const MAX_EMOJI_CHARS: usize = 8;
+
+pub fn is_valid_emoji(emoji: &str) -> bool {
+    !emoji.is_empty()
+        && emoji.chars().count() <= MAX_EMOJI_CHARS
+        && !emoji
+            .chars()
+            .any(|c| c.is_ascii_alphanumeric() || c.is_whitespace())
+}
+
+// ---- one row per message, user and emoji
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReactionData {
+    pub msg_uuid: String,
+    pub user_uuid: String,
+    pub emoji: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ReactionData {
+    pub fn new(
+        msg_uuid: String,
+        user_uuid: String,
+        emoji: String,
+        created_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            msg_uuid,
+            user_uuid,
+            emoji,
+            created_at,
+        }
+    }
+}
+
+// ---- reactions of a message grouped by emoji, in the order each emoji was first used
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReactionSummary {
+    pub emoji: String,
+    pub count: usize,
+    pub users: Vec<User>,
+}
+
+impl ReactionSummary {
+    // `vec_reaction` is expected oldest first
+    pub fn aggregate(vec_reaction: &[ReactionData], users: &HashMap<String, User>) -> Vec<Self> {
+        let mut vec_summary = Vec::<Self>::new();
+        for reaction in vec_reaction {
+            let user = users
+                .get(&reaction.user_uuid)
+                .cloned()
+                .unwrap_or_else(|| User {
+                    uuid: reaction.user_uuid.clone(),
+                    ..Default::default()
+                });
+            match vec_summary
+                .iter_mut()
+                .find(|summary| summary.emoji == reaction.emoji)
+            {
+                Some(summary) => {
+                    summary.count += 1;
+                    summary.users.push(user);
+                }
+                None => vec_summary.push(Self {
+                    emoji: reaction.emoji.clone(),
+                    count: 1,
+                    users: vec![user],
+                }),
+            }
+        }
+        vec_summary
+    }
+
+    pub fn reacted_by(&self, user_uuid: &str) -> bool {
+        self.users.iter().any(|user| user.uuid == user_uuid)
+    }
+}
+
+#[cfg(feature = "ssr")]
+use {
+    crate::{error::ServerError, state::repository::Repository},
+    futures::future::join_all,
+};
+
+#[cfg(feature = "ssr")]
+impl ReactionSummary {
+    // summaries keyed by message uuid, messages without reactions are left out
+    pub async fn load(
+        msg_uuids: &[String],
+        repository: &Repository,
+    ) -> Result<HashMap<String, Vec<Self>>, ServerError> {
+        let vec_reaction = repository.reactions.get_reactions(msg_uuids).await?;
+
+        let mut user_uuids = vec_reaction
+            .iter()
+            .map(|reaction| reaction.user_uuid.clone())
+            .collect::<Vec<String>>();
+        user_uuids.sort();
+        user_uuids.dedup();
+        let future_vec = user_uuids
+            .iter()
+            .map(|uuid| repository.users.get_user_by_uuid(uuid));
+        let users = join_all(future_vec)
+            .await
+            .into_iter()
+            .filter_map(|result| result.ok().flatten())
+            .map(|user_data| (user_data.uuid.clone(), User::from_user_data(&user_data)))
+            .collect::<HashMap<String, User>>();
+
+        let mut by_msg = HashMap::<String, Vec<ReactionData>>::new();
+        for reaction in vec_reaction {
+            by_msg
+                .entry(reaction.msg_uuid.clone())
+                .or_default()
+                .push(reaction);
+        }
+        Ok(by_msg
+            .into_iter()
+            .map(|(msg_uuid, vec_reaction)| (msg_uuid, Self::aggregate(&vec_reaction, &users)))
+            .collect())
+    }
+}
@@ -0,0 +1,102 @@
+use super::{message_model::MsgCursor, user_model::User};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ---- the last message a user has read in a room
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReadMarker {
+    pub user_uuid: String,
+    pub room_uuid: String,
+    pub last_read_at: DateTime<Utc>,
+    pub last_read_msg: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReadMarker {
+    pub fn new(
+        user_uuid: String,
+        room_uuid: String,
+        cursor: &MsgCursor,
+        updated_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            user_uuid,
+            room_uuid,
+            last_read_at: cursor.created_at,
+            last_read_msg: cursor.msg_uuid.clone(),
+            updated_at,
+        }
+    }
+
+    // one marker per user and room
+    pub fn id(&self) -> String {
+        format!("{}_{}", self.user_uuid, self.room_uuid)
+    }
+
+    pub fn cursor(&self) -> MsgCursor {
+        MsgCursor {
+            created_at: self.last_read_at,
+            msg_uuid: self.last_read_msg.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub struct UnreadCount {
+    pub unread: usize,
+    pub mentions: usize,
+}
+
+// ---- how far another member has read, shown as "seen by"
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReadReceipt {
+    pub user: User,
+    pub cursor: MsgCursor,
+}
+
+#[cfg(feature = "ssr")]
+use crate::{error::ServerError, state::repository::Repository};
+
+#[cfg(feature = "ssr")]
+impl ReadMarker {
+    // moves the marker of the user forward to the message, returns the new cursor when it moved.
+    // the cursor comes from the stored message, a client can't place it at a time of its choosing
+    pub async fn advance(
+        user_uuid: &str,
+        room_uuid: &str,
+        msg_uuid: &str,
+        repository: &Repository,
+    ) -> Result<Option<MsgCursor>, ServerError> {
+        let cursor = repository
+            .messages
+            .get_msg(msg_uuid)
+            .await?
+            .filter(|msg_data| msg_data.channel == room_uuid)
+            .map(|msg_data| MsgCursor {
+                created_at: msg_data.created_at,
+                msg_uuid: msg_data.msg_uuid,
+            })
+            .ok_or(ServerError::MessageDoesNotExist)?;
+        let current = repository
+            .read_markers
+            .get_read_marker(user_uuid, room_uuid)
+            .await?;
+        if current.is_some_and(|read_marker| read_marker.cursor() >= cursor) {
+            return Ok(None);
+        }
+
+        let read_marker = ReadMarker::new(
+            user_uuid.to_string(),
+            room_uuid.to_string(),
+            &cursor,
+            Utc::now(),
+        );
+        repository
+            .read_markers
+            .upsert_read_marker(&read_marker)
+            .await?;
+        Ok(Some(cursor))
+    }
+}
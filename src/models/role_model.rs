@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// what a member may do in a room, ordered from the most to the least powerful
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Owner,
+    Admin,
+    #[default]
+    Member,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Post,
+    Invite,
+    Kick,
+    DeleteMessages,
+    EditSettings,
+    DeleteRoom,
+}
+
+impl Role {
+    // ---- the permission matrix, every room check goes through here
+    pub fn can(&self, permission: Permission) -> bool {
+        match self {
+            Self::Owner => true,
+            Self::Admin => !matches!(permission, Permission::DeleteRoom),
+            Self::Member => matches!(permission, Permission::Post | Permission::Invite),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Admin => "admin",
+            Self::Member => "member",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self {
+            Self::Post => "Post Messages",
+            Self::Invite => "Invite Users",
+            Self::Kick => "Kick Members",
+            Self::DeleteMessages => "Delete Messages",
+            Self::EditSettings => "Edit The Settings",
+            Self::DeleteRoom => "Delete The Room",
+        };
+        write!(f, "{}", action)
+    }
+}
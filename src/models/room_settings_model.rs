@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+pub const ROOM_NAME_MAX: usize = 64;
+pub const TOPIC_MAX: usize = 120;
+pub const DESCRIPTION_MAX: usize = 500;
+
+// ---- what moderators can change about a channel after creating it
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RoomSettings {
+    pub room_name: String,
+    // one line shown in the header
+    pub topic: String,
+    // what the directory shows about the room
+    pub description: String,
+    pub public: bool,
+    // archived rooms are read only and leave the directory
+    pub archived: bool,
+}
+
+impl RoomSettings {
+    // surrounding whitespace never counts
+    pub fn trimmed(self) -> Self {
+        Self {
+            room_name: self.room_name.trim().to_string(),
+            topic: self.topic.trim().to_string(),
+            description: self.description.trim().to_string(),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+use crate::error::ServerError;
+
+#[cfg(feature = "ssr")]
+impl RoomSettings {
+    pub fn validate(&self) -> Result<(), ServerError> {
+        if self.room_name.is_empty() || self.room_name.chars().count() > ROOM_NAME_MAX {
+            return Err(ServerError::InvalidRoomSettings(
+                "the name takes 1 to 64 characters",
+            ));
+        }
+        if self.topic.chars().count() > TOPIC_MAX {
+            return Err(ServerError::InvalidRoomSettings(
+                "the topic takes at most 120 characters",
+            ));
+        }
+        if self.description.chars().count() > DESCRIPTION_MAX {
+            return Err(ServerError::InvalidRoomSettings(
+                "the description takes at most 500 characters",
+            ));
+        }
+        Ok(())
+    }
+}
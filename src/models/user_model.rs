@@ -70,9 +70,6 @@ impl User {
     }
 }
 
-#[cfg(feature = "ssr")]
-use crate::{error::ServerError, state::db::Database};
-
 #[cfg(feature = "ssr")]
 impl UserData {
     pub fn new(
@@ -96,102 +93,4 @@ impl UserData {
             created_at,
         }
     }
-
-    pub async fn insert_into_db(&self, pool: &Database) -> Result<(), ServerError> {
-        if pool
-            .client
-            .query("SELECT * FROM user_data WHERE email = $email")
-            .bind(("email", &self.email))
-            .await?
-            .take::<Option<Self>>(0)
-            .unwrap_or(None)
-            .is_some()
-        {
-            return Err(ServerError::EmailTaken);
-        }
-
-        match pool
-            .client
-            .create::<Option<Self>>(("user_data", &self.uuid))
-            .content(self)
-            .await
-        {
-            Ok(_) => Ok(()),
-            Err(err) => Err(ServerError::DatabaseError(err)),
-        }
-    }
-
-    pub async fn add_channel(&self, room_uuid: String, pool: &Database) -> Result<(), ServerError> {
-        let find_entry = pool
-            .client
-            .select::<Option<Self>>(("user_data", &self.uuid))
-            .await?;
-
-        if let Some(user_data) = find_entry {
-            if user_data.joined_channels.contains(&room_uuid) {
-                return Err(ServerError::AddChannelError);
-            }
-
-            pool.client
-                .update::<Option<Self>>(("user_data", &self.uuid))
-                .patch(surrealdb::opt::PatchOp::add("/joined_channels", room_uuid))
-                .await?;
-        }
-        Ok(())
-    }
-
-    pub async fn remove_channel(
-        &self,
-        channel: String,
-        pool: &Database,
-    ) -> Result<(), ServerError> {
-        let find_entry = pool
-            .client
-            .select::<Option<Self>>(("user_data", &self.uuid))
-            .await?;
-
-        if let Some(mut user_data) = find_entry {
-            if !user_data.joined_channels.contains(&channel) {
-                return Err(ServerError::RemoveChannelError);
-            }
-            user_data
-                .joined_channels
-                .retain(|room_uuid| *room_uuid != channel);
-
-            pool.client
-                .update::<Option<Self>>(("user_data", &self.uuid))
-                .merge(user_data)
-                .await?;
-        }
-
-        Ok(())
-    }
-
-    pub async fn get_from_email(email: &str, pool: &Database) -> Result<Option<Self>, ServerError> {
-        match pool
-            .client
-            .query("SELECT * FROM type::table($table) WHERE email = $email")
-            .bind(("table", "user_data"))
-            .bind(("email", email))
-            .await
-        {
-            Ok(mut maybe_user) => maybe_user
-                .take::<Option<Self>>(0)
-                .map_err(|err| ServerError::DatabaseError(err)),
-            Err(err) => Err(ServerError::DatabaseError(err)),
-        }
-    }
-
-    pub async fn get_from_uuid(uuid: &str, pool: &Database) -> Option<Self> {
-        match pool
-            .client
-            .query("SELECT * FROM type::table($table) WHERE uuid = $uuid")
-            .bind(("table", "user_data"))
-            .bind(("uuid", uuid))
-            .await
-        {
-            Ok(mut maybe_user) => maybe_user.take::<Option<Self>>(0).unwrap_or(None),
-            Err(_) => None,
-        }
-    }
 }
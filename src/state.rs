@@ -1,5 +1,9 @@
 pub mod auth;
+pub mod config;
 pub mod db;
+pub mod migrations;
+pub mod presence;
+pub mod repository;
 pub mod rooms_manager;
 
 #[cfg(feature = "ssr")]
@@ -7,23 +11,23 @@ use {
     self::rooms_manager::RoomsManager,
     auth::AuthSession,
     axum::extract::FromRef,
-    db::Database,
     leptos::{use_context, LeptosOptions, ServerFnError},
     leptos_router::RouteListing,
+    repository::Repository,
 };
 
 #[cfg(feature = "ssr")]
 #[derive(Clone, Debug, FromRef)]
 pub struct AppState {
-    pub pool: Database,
+    pub repository: Repository,
     pub leptos_options: LeptosOptions,
     pub routes: Vec<RouteListing>,
     pub rooms_manager: RoomsManager,
 }
 
 #[cfg(feature = "ssr")]
-pub fn pool() -> Result<Database, ServerFnError> {
-    use_context::<Database>().ok_or_else(|| ServerFnError::new("No database is detected!"))
+pub fn repository() -> Result<Repository, ServerFnError> {
+    use_context::<Repository>().ok_or_else(|| ServerFnError::new("No repository is detected!"))
 }
 
 #[cfg(feature = "ssr")]
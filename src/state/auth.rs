@@ -1,15 +1,15 @@
 #[cfg(feature = "ssr")]
 use {
-    crate::models::user_model::{User, UserData},
-    crate::state::db::Database,
+    crate::models::user_model::User,
+    crate::state::{db::Database, repository::UserRepository},
     async_trait::async_trait,
     axum_session_auth::{Authentication, SessionSurrealPool},
-    surrealdb::engine::remote::ws::Client,
+    surrealdb::engine::any::Any,
 };
 
 #[cfg(feature = "ssr")]
 pub type AuthSession =
-    axum_session_auth::AuthSession<User, String, SessionSurrealPool<Client>, Database>;
+    axum_session_auth::AuthSession<User, String, SessionSurrealPool<Any>, Database>;
 
 #[cfg(feature = "ssr")]
 #[async_trait]
@@ -17,8 +17,9 @@ impl Authentication<User, String, Database> for User {
     async fn load_user(userid: String, pool: Option<&Database>) -> Result<User, anyhow::Error> {
         let pool = pool.expect("Pool doesn't exist!");
 
-        let user_data = UserData::get_from_uuid(&userid, &pool)
-            .await
+        let user_data = pool
+            .get_user_by_uuid(&userid)
+            .await?
             .ok_or_else(|| anyhow::anyhow!("User does not exist"))?;
 
         Ok(User::from_user_data(&user_data))
@@ -0,0 +1,208 @@
+#[cfg(feature = "ssr")]
+use {
+    crate::error::ServerError,
+    serde::Deserialize,
+    std::{path::Path, time::Duration},
+};
+
+#[cfg(feature = "ssr")]
+const DEFAULT_CONFIG_PATH: &str = "hey.toml";
+
+#[cfg(feature = "ssr")]
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+// ---- validated configuration handed to the server on boot
+
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    pub database: DatabaseConfig,
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug)]
+pub struct DatabaseConfig {
+    pub endpoint: String,
+    pub credentials: Option<Credentials>,
+    pub namespace: String,
+    pub database: String,
+    pub tls: bool,
+    pub connect_timeout: Duration,
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[cfg(feature = "ssr")]
+impl DatabaseConfig {
+    /// In memory engine, handy for local development and tests.
+    pub fn memory() -> Self {
+        Self {
+            endpoint: "mem://".to_string(),
+            credentials: None,
+            namespace: "admin".to_string(),
+            database: "hey!".to_string(),
+            tls: false,
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+        }
+    }
+
+    /// Full endpoint for `surrealdb::engine::any::connect`. A bare `host:port`
+    /// is treated as a remote server and gets `ws://` or `wss://` depending on `tls`.
+    pub fn endpoint_url(&self) -> String {
+        if self.endpoint.contains("://") {
+            self.endpoint.clone()
+        } else if self.tls {
+            format!("wss://{}", self.endpoint)
+        } else {
+            format!("ws://{}", self.endpoint)
+        }
+    }
+}
+
+// ---- raw values as they are read from the file, everything is optional here
+// ---- so that the env can fill in the gaps before validation
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Default, Deserialize)]
+struct RawServerConfig {
+    #[serde(default)]
+    database: RawDatabaseConfig,
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Default, Deserialize)]
+struct RawDatabaseConfig {
+    endpoint: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    namespace: Option<String>,
+    database: Option<String>,
+    tls: Option<bool>,
+    connect_timeout_secs: Option<u64>,
+}
+
+#[cfg(feature = "ssr")]
+impl ServerConfig {
+    /// Reads `hey.toml` (or the file pointed by `HEY_CONFIG`) and applies the
+    /// `HEY_DB_*` environment variables on top of it.
+    pub fn load() -> Result<Self, ServerError> {
+        match std::env::var("HEY_CONFIG") {
+            Ok(path) => Self::from_file(path, true),
+            Err(_) => Self::from_file(DEFAULT_CONFIG_PATH, false),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>, required: bool) -> Result<Self, ServerError> {
+        let path = path.as_ref();
+        let raw = if path.exists() {
+            let content = std::fs::read_to_string(path).map_err(|err| {
+                ServerError::InvalidConfig(format!("unable to read {}: {}", path.display(), err))
+            })?;
+            toml::from_str::<RawServerConfig>(&content).map_err(|err| {
+                ServerError::InvalidConfig(format!("unable to parse {}: {}", path.display(), err))
+            })?
+        } else if required {
+            return Err(ServerError::InvalidConfig(format!(
+                "config file {} does not exist",
+                path.display()
+            )));
+        } else {
+            RawServerConfig::default()
+        };
+
+        raw.merge_env()?.validate()
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl RawServerConfig {
+    fn merge_env(mut self) -> Result<Self, ServerError> {
+        let db = &mut self.database;
+        if let Some(endpoint) = env_var("HEY_DB_ENDPOINT") {
+            db.endpoint = Some(endpoint);
+        }
+        if let Some(username) = env_var("HEY_DB_USERNAME") {
+            db.username = Some(username);
+        }
+        if let Some(password) = env_var("HEY_DB_PASSWORD") {
+            db.password = Some(password);
+        }
+        if let Some(namespace) = env_var("HEY_DB_NAMESPACE") {
+            db.namespace = Some(namespace);
+        }
+        if let Some(database) = env_var("HEY_DB_NAME") {
+            db.database = Some(database);
+        }
+        if let Some(tls) = env_var("HEY_DB_TLS") {
+            let tls = tls
+                .parse::<bool>()
+                .map_err(|_| ServerError::InvalidConfig(format!("HEY_DB_TLS: {}", tls)))?;
+            db.tls = Some(tls);
+        }
+        if let Some(secs) = env_var("HEY_DB_CONNECT_TIMEOUT") {
+            let secs = secs.parse::<u64>().map_err(|_| {
+                ServerError::InvalidConfig(format!("HEY_DB_CONNECT_TIMEOUT: {}", secs))
+            })?;
+            db.connect_timeout_secs = Some(secs);
+        }
+        Ok(self)
+    }
+
+    fn validate(self) -> Result<ServerConfig, ServerError> {
+        let db = self.database;
+        let connect_timeout_secs = db
+            .connect_timeout_secs
+            .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS);
+        if connect_timeout_secs == 0 {
+            return Err(ServerError::InvalidConfig(
+                "database.connect_timeout_secs must be greater than 0".to_string(),
+            ));
+        }
+
+        let endpoint = required(db.endpoint, "database.endpoint")?;
+        // embedded engines run in process, there is nobody to sign in to
+        let credentials = if is_embedded(&endpoint) {
+            None
+        } else {
+            Some(Credentials {
+                username: required(db.username, "database.username")?,
+                password: required(db.password, "database.password")?,
+            })
+        };
+
+        let database = DatabaseConfig {
+            endpoint,
+            credentials,
+            namespace: required(db.namespace, "database.namespace")?,
+            database: required(db.database, "database.database")?,
+            tls: db.tls.unwrap_or(false),
+            connect_timeout: Duration::from_secs(connect_timeout_secs),
+        };
+
+        Ok(ServerConfig { database })
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn is_embedded(endpoint: &str) -> bool {
+    ["mem://", "memory", "file://", "rocksdb://"]
+        .iter()
+        .any(|scheme| endpoint.starts_with(scheme))
+}
+
+#[cfg(feature = "ssr")]
+fn env_var(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.is_empty())
+}
+
+#[cfg(feature = "ssr")]
+fn required(value: Option<String>, key: &'static str) -> Result<String, ServerError> {
+    value
+        .filter(|value| !value.is_empty())
+        .ok_or(ServerError::MissingConfig(key))
+}
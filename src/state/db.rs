@@ -1,8 +1,8 @@
 #[cfg(feature = "ssr")]
 use {
-    crate::error::ServerError,
+    crate::{error::ServerError, state::config::DatabaseConfig},
     surrealdb::{
-        engine::remote::ws::{Client, Ws},
+        engine::any::{self, Any},
         opt::auth::Root,
         Surreal,
     },
@@ -11,30 +11,47 @@ use {
 #[cfg(feature = "ssr")]
 #[derive(Clone, Debug)]
 pub struct Database {
-    pub client: Surreal<Client>,
+    pub client: Surreal<Any>,
     #[allow(dead_code)]
-    name_space: &'static str,
+    name_space: String,
     #[allow(dead_code)]
-    db_name: &'static str,
+    db_name: String,
 }
 
 #[cfg(feature = "ssr")]
 impl Database {
-    pub async fn init() -> Result<Self, ServerError> {
-        let client = Surreal::new::<Ws>("0.0.0.0:8000").await?;
-        client
-            .signin(Root {
-                username: "root",
-                password: "root",
-            })
-            .await?;
+    pub async fn init(config: &DatabaseConfig) -> Result<Self, ServerError> {
+        let connect = async {
+            let client = any::connect(config.endpoint_url()).await?;
+            if let Some(credentials) = &config.credentials {
+                client
+                    .signin(Root {
+                        username: &credentials.username,
+                        password: &credentials.password,
+                    })
+                    .await?;
+            }
 
-        client.use_ns("admin").use_db("hey!").await?;
+            client
+                .use_ns(&config.namespace)
+                .use_db(&config.database)
+                .await?;
+
+            Ok::<Surreal<Any>, ServerError>(client)
+        };
+
+        let client = tokio::time::timeout(config.connect_timeout, connect)
+            .await
+            .map_err(|_| ServerError::ConnectTimeout(config.endpoint.clone()))??;
 
         Ok(Self {
             client,
-            name_space: "admin",
-            db_name: "hey!",
+            name_space: config.namespace.clone(),
+            db_name: config.database.clone(),
         })
     }
+
+    pub async fn memory() -> Result<Self, ServerError> {
+        Self::init(&DatabaseConfig::memory()).await
+    }
 }
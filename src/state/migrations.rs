@@ -0,0 +1,181 @@
+#[cfg(feature = "ssr")]
+use {
+    crate::{error::ServerError, state::db::Database},
+    serde::Deserialize,
+};
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub statements: &'static str,
+}
+
+// ---- every migration goes here in order, an applied migration must never be edited,
+// ---- add a new file instead
+
+#[cfg(feature = "ssr")]
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        statements: include_str!("../../migrations/0001_initial_schema.surql"),
+    },
+    Migration {
+        version: 2,
+        name: "message_edits",
+        statements: include_str!("../../migrations/0002_message_edits.surql"),
+    },
+    Migration {
+        version: 3,
+        name: "message_tombstones",
+        statements: include_str!("../../migrations/0003_message_tombstones.surql"),
+    },
+    Migration {
+        version: 4,
+        name: "reactions",
+        statements: include_str!("../../migrations/0004_reactions.surql"),
+    },
+    Migration {
+        version: 5,
+        name: "threads",
+        statements: include_str!("../../migrations/0005_threads.surql"),
+    },
+    Migration {
+        version: 6,
+        name: "read_markers",
+        statements: include_str!("../../migrations/0006_read_markers.surql"),
+    },
+    Migration {
+        version: 7,
+        name: "read_receipts",
+        statements: include_str!("../../migrations/0007_read_receipts.surql"),
+    },
+    Migration {
+        version: 8,
+        name: "mentions",
+        statements: include_str!("../../migrations/0008_mentions.surql"),
+    },
+    Migration {
+        version: 9,
+        name: "direct_rooms",
+        statements: include_str!("../../migrations/0009_direct_rooms.surql"),
+    },
+    Migration {
+        version: 10,
+        name: "group_rooms",
+        statements: include_str!("../../migrations/0010_group_rooms.surql"),
+    },
+    Migration {
+        version: 11,
+        name: "room_roles",
+        statements: include_str!("../../migrations/0011_room_roles.surql"),
+    },
+    Migration {
+        version: 12,
+        name: "room_bans",
+        statements: include_str!("../../migrations/0012_room_bans.surql"),
+    },
+    Migration {
+        version: 13,
+        name: "invites",
+        statements: include_str!("../../migrations/0013_invites.surql"),
+    },
+    Migration {
+        version: 14,
+        name: "public_rooms",
+        statements: include_str!("../../migrations/0014_public_rooms.surql"),
+    },
+    Migration {
+        version: 15,
+        name: "room_settings",
+        statements: include_str!("../../migrations/0015_room_settings.surql"),
+    },
+    Migration {
+        version: 16,
+        name: "room_versions",
+        statements: include_str!("../../migrations/0016_room_versions.surql"),
+    },
+];
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Deserialize)]
+struct AppliedMigration {
+    version: u32,
+}
+
+#[cfg(feature = "ssr")]
+pub async fn run(pool: &Database) -> Result<Vec<Migration>, ServerError> {
+    // on a fresh database the `migration` table doesn't exist yet, which reads as empty
+    let applied = pool
+        .client
+        .query("SELECT version FROM migration")
+        .await?
+        .take::<Vec<AppliedMigration>>(0)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect::<Vec<u32>>();
+
+    let mut newly_applied = Vec::new();
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|migration| !applied.contains(&migration.version))
+    {
+        pool.client
+            .query("BEGIN TRANSACTION")
+            .query(migration.statements)
+            .query(
+                "CREATE type::thing('migration', $version) CONTENT {
+                    version: $version,
+                    name: $name,
+                    applied_at: time::now()
+                }",
+            )
+            .query("COMMIT TRANSACTION")
+            .bind(("version", migration.version))
+            .bind(("name", migration.name))
+            .await?
+            .check()
+            .map_err(|err| {
+                ServerError::MigrationFailed(format!(
+                    "{:04}_{}: {}",
+                    migration.version, migration.name, err
+                ))
+            })?;
+        newly_applied.push(*migration);
+    }
+
+    Ok(newly_applied)
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_run_in_order_without_gaps() {
+        for (idx, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, idx + 1, "{}", migration.name);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_second_run_applies_nothing() {
+        let pool = Database::memory().await.unwrap();
+        let first = run(&pool).await.unwrap();
+        assert_eq!(first.len(), MIGRATIONS.len());
+        let second = run(&pool).await.unwrap();
+        assert!(second.is_empty());
+
+        let applied = pool
+            .client
+            .query("SELECT version FROM migration")
+            .await
+            .unwrap()
+            .take::<Vec<AppliedMigration>>(0)
+            .unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+    }
+}
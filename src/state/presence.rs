@@ -0,0 +1,72 @@
+#[cfg(feature = "ssr")]
+use {
+    crate::models::presence_model::PresenceStatus,
+    std::{
+        collections::HashMap,
+        sync::{Arc, RwLock},
+    },
+};
+
+// ---- a user is online while one of their connections is active,
+// ---- idle while all of them report idle and offline without any connection
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, Default)]
+pub struct PresenceTracker {
+    // user uuid -> connection id -> whether that connection reported idle
+    connections: Arc<RwLock<HashMap<String, HashMap<String, bool>>>>,
+}
+
+#[cfg(feature = "ssr")]
+impl PresenceTracker {
+    pub fn status(&self, user_uuid: &str) -> PresenceStatus {
+        let connections = self.connections.read().unwrap();
+        Self::status_of(connections.get(user_uuid))
+    }
+
+    // the methods below return the new status when the change altered it
+
+    pub fn connect(&self, user_uuid: &str, conn_id: &str) -> Option<PresenceStatus> {
+        self.update(user_uuid, |user_connections| {
+            user_connections.insert(conn_id.to_string(), false);
+        })
+    }
+
+    pub fn heartbeat(&self, user_uuid: &str, conn_id: &str, idle: bool) -> Option<PresenceStatus> {
+        self.update(user_uuid, |user_connections| {
+            user_connections.insert(conn_id.to_string(), idle);
+        })
+    }
+
+    pub fn disconnect(&self, user_uuid: &str, conn_id: &str) -> Option<PresenceStatus> {
+        self.update(user_uuid, |user_connections| {
+            user_connections.remove(conn_id);
+        })
+    }
+
+    fn update(
+        &self,
+        user_uuid: &str,
+        change: impl FnOnce(&mut HashMap<String, bool>),
+    ) -> Option<PresenceStatus> {
+        let mut connections = self.connections.write().unwrap();
+        let before = Self::status_of(connections.get(user_uuid));
+        let user_connections = connections.entry(user_uuid.to_string()).or_default();
+        change(user_connections);
+        if user_connections.is_empty() {
+            connections.remove(user_uuid);
+        }
+        let after = Self::status_of(connections.get(user_uuid));
+        (before != after).then_some(after)
+    }
+
+    fn status_of(user_connections: Option<&HashMap<String, bool>>) -> PresenceStatus {
+        match user_connections {
+            Some(user_connections) if user_connections.values().any(|idle| !idle) => {
+                PresenceStatus::Online
+            }
+            Some(user_connections) if !user_connections.is_empty() => PresenceStatus::Idle,
+            _ => PresenceStatus::Offline,
+        }
+    }
+}
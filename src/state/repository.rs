@@ -0,0 +1,206 @@
+pub mod memory;
+pub mod surreal;
+
+#[cfg(feature = "ssr")]
+use {
+    self::memory::MemoryRepository,
+    crate::{
+        error::ServerError,
+        models::{
+            invite_model::Invite,
+            message_model::{MsgCursor, MsgData, MsgRevision},
+            reaction_model::ReactionData,
+            read_marker_model::{ReadMarker, UnreadCount},
+            user_model::UserData,
+        },
+        state::{db::Database, rooms_manager::RoomData},
+    },
+    async_trait::async_trait,
+    chrono::{DateTime, Utc},
+    std::{collections::HashMap, fmt::Debug, sync::Arc},
+};
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+pub trait UserRepository: Debug + Send + Sync {
+    async fn insert_user(&self, user_data: &UserData) -> Result<(), ServerError>;
+
+    async fn get_user_by_uuid(&self, uuid: &str) -> Result<Option<UserData>, ServerError>;
+
+    async fn get_user_by_email(&self, email: &str) -> Result<Option<UserData>, ServerError>;
+
+    // users whose name contains `query`, ignoring case, sorted by name
+    async fn search_users(&self, query: &str, limit: usize) -> Result<Vec<UserData>, ServerError>;
+
+    async fn add_channel(&self, user_uuid: &str, room_uuid: &str) -> Result<(), ServerError>;
+
+    async fn remove_channel(&self, user_uuid: &str, room_uuid: &str) -> Result<(), ServerError>;
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+pub trait RoomRepository: Debug + Send + Sync {
+    async fn insert_room(&self, room_data: &RoomData) -> Result<(), ServerError>;
+
+    async fn get_room(&self, room_uuid: &str) -> Result<Option<RoomData>, ServerError>;
+
+    // writes the room only when the stored version is still the one `room_data` was read at,
+    // bumping it, returns whether it was written
+    async fn update_room(&self, room_data: &RoomData) -> Result<bool, ServerError>;
+
+    // the room along with its invites and the read markers of its members
+    async fn delete_room(&self, room_uuid: &str) -> Result<(), ServerError>;
+
+    // public channels whose name or description contains `query`, ignoring case,
+    // an empty query matches them all. sorted by name, `start` skips the pages already shown
+    async fn search_public_rooms(
+        &self,
+        query: &str,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<RoomData>, ServerError>;
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+pub trait MessageRepository: Debug + Send + Sync {
+    async fn insert_msg(&self, msg_data: &MsgData) -> Result<(), ServerError>;
+
+    async fn get_msg(&self, msg_uuid: &str) -> Result<Option<MsgData>, ServerError>;
+
+    async fn update_msg(&self, msg_data: &MsgData) -> Result<(), ServerError>;
+
+    // stores the tombstone left by `MsgData::tombstone` and drops the revisions
+    // and reactions of the message
+    async fn tombstone_msg(&self, msg_data: &MsgData) -> Result<(), ServerError>;
+
+    async fn insert_revision(&self, revision: &MsgRevision) -> Result<(), ServerError>;
+
+    // oldest first
+    async fn get_revisions(&self, msg_uuid: &str) -> Result<Vec<MsgRevision>, ServerError>;
+
+    // every message of the room along with their revisions and reactions
+    async fn delete_room_msgs(&self, room_uuid: &str) -> Result<(), ServerError>;
+
+    // walks backward from `before` when only it is given (or from the latest message when
+    // no cursor is given), forward from `after` otherwise, rows come in that walking order,
+    // only top level messages are walked when `parent` is none, only its replies otherwise
+    async fn get_msg_page(
+        &self,
+        room_uuid: &str,
+        parent: Option<&str>,
+        before: Option<&MsgCursor>,
+        after: Option<&MsgCursor>,
+        limit: usize,
+    ) -> Result<Vec<MsgData>, ServerError>;
+
+    // top level messages of the room past `after` that weren't sent by `user_uuid`,
+    // with how many of them mention the user, everything counts when `after` is none
+    async fn count_unread(
+        &self,
+        room_uuid: &str,
+        user_uuid: &str,
+        after: Option<&MsgCursor>,
+    ) -> Result<UnreadCount, ServerError>;
+
+    // number of replies keyed by parent uuid, messages without replies are left out
+    async fn count_replies(
+        &self,
+        msg_uuids: &[String],
+    ) -> Result<HashMap<String, usize>, ServerError>;
+
+    // time of the latest message keyed by room uuid, rooms without messages are left out
+    async fn last_activity(
+        &self,
+        room_uuids: &[String],
+    ) -> Result<HashMap<String, DateTime<Utc>>, ServerError>;
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+pub trait ReactionRepository: Debug + Send + Sync {
+    async fn insert_reaction(&self, reaction: &ReactionData) -> Result<(), ServerError>;
+
+    async fn delete_reaction(
+        &self,
+        msg_uuid: &str,
+        user_uuid: &str,
+        emoji: &str,
+    ) -> Result<(), ServerError>;
+
+    // reactions of all the given messages, oldest first
+    async fn get_reactions(&self, msg_uuids: &[String]) -> Result<Vec<ReactionData>, ServerError>;
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+pub trait ReadMarkerRepository: Debug + Send + Sync {
+    async fn get_read_marker(
+        &self,
+        user_uuid: &str,
+        room_uuid: &str,
+    ) -> Result<Option<ReadMarker>, ServerError>;
+
+    async fn get_room_read_markers(&self, room_uuid: &str) -> Result<Vec<ReadMarker>, ServerError>;
+
+    // creates the marker or moves the existing one
+    async fn upsert_read_marker(&self, read_marker: &ReadMarker) -> Result<(), ServerError>;
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+pub trait InviteRepository: Debug + Send + Sync {
+    async fn insert_invite(&self, invite: &Invite) -> Result<(), ServerError>;
+
+    async fn get_invite(&self, code: &str) -> Result<Option<Invite>, ServerError>;
+
+    // newest first
+    async fn get_room_invites(&self, room_uuid: &str) -> Result<Vec<Invite>, ServerError>;
+
+    // takes one use of the invite if it's still usable at `now`, returns whether it was,
+    // checked and counted in one step so two people can't take the last use
+    async fn redeem_invite(&self, code: &str, now: DateTime<Utc>) -> Result<bool, ServerError>;
+
+    async fn revoke_invite(&self, code: &str, revoked_at: DateTime<Utc>)
+        -> Result<(), ServerError>;
+}
+
+// ---- the handle server functions and the websocket work with,
+// ---- they never see which storage is behind it
+
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug)]
+pub struct Repository {
+    pub users: Arc<dyn UserRepository>,
+    pub rooms: Arc<dyn RoomRepository>,
+    pub messages: Arc<dyn MessageRepository>,
+    pub reactions: Arc<dyn ReactionRepository>,
+    pub read_markers: Arc<dyn ReadMarkerRepository>,
+    pub invites: Arc<dyn InviteRepository>,
+}
+
+#[cfg(feature = "ssr")]
+impl Repository {
+    pub fn surreal(pool: Database) -> Self {
+        Self {
+            users: Arc::new(pool.clone()),
+            rooms: Arc::new(pool.clone()),
+            messages: Arc::new(pool.clone()),
+            reactions: Arc::new(pool.clone()),
+            read_markers: Arc::new(pool.clone()),
+            invites: Arc::new(pool),
+        }
+    }
+
+    pub fn memory() -> Self {
+        let store = MemoryRepository::default();
+        Self {
+            users: Arc::new(store.clone()),
+            rooms: Arc::new(store.clone()),
+            messages: Arc::new(store.clone()),
+            reactions: Arc::new(store.clone()),
+            read_markers: Arc::new(store.clone()),
+            invites: Arc::new(store),
+        }
+    }
+}
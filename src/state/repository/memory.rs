@@ -0,0 +1,662 @@
+#[cfg(feature = "ssr")]
+use {
+    super::{
+        InviteRepository, MessageRepository, ReactionRepository, ReadMarkerRepository,
+        RoomRepository, UserRepository,
+    },
+    crate::{
+        error::ServerError,
+        models::{
+            invite_model::Invite,
+            message_model::{MsgCursor, MsgData, MsgRevision},
+            reaction_model::ReactionData,
+            read_marker_model::{ReadMarker, UnreadCount},
+            user_model::UserData,
+        },
+        state::rooms_manager::{RoomData, RoomKind},
+    },
+    async_trait::async_trait,
+    chrono::{DateTime, Utc},
+    std::{
+        collections::HashMap,
+        sync::{Arc, RwLock},
+    },
+};
+
+// ---- storage living in the process memory, used for tests and
+// ---- to run the server side logic without a database
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, Default)]
+pub struct MemoryRepository {
+    users: Arc<RwLock<HashMap<String, UserData>>>,
+    rooms: Arc<RwLock<HashMap<String, RoomData>>>,
+    messages: Arc<RwLock<HashMap<String, MsgData>>>,
+    revisions: Arc<RwLock<Vec<MsgRevision>>>,
+    // kept in insertion order, which is the oldest first
+    reactions: Arc<RwLock<Vec<ReactionData>>>,
+    read_markers: Arc<RwLock<HashMap<String, ReadMarker>>>,
+    invites: Arc<RwLock<HashMap<String, Invite>>>,
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl UserRepository for MemoryRepository {
+    async fn insert_user(&self, user_data: &UserData) -> Result<(), ServerError> {
+        let mut users = self.users.write().unwrap();
+        if users.values().any(|user| user.email == user_data.email) {
+            return Err(ServerError::EmailTaken);
+        }
+        users.insert(user_data.uuid.clone(), user_data.clone());
+        Ok(())
+    }
+
+    async fn get_user_by_uuid(&self, uuid: &str) -> Result<Option<UserData>, ServerError> {
+        let users = self.users.read().unwrap();
+        Ok(users.get(uuid).cloned())
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> Result<Option<UserData>, ServerError> {
+        let users = self.users.read().unwrap();
+        Ok(users.values().find(|user| user.email == email).cloned())
+    }
+
+    async fn search_users(&self, query: &str, limit: usize) -> Result<Vec<UserData>, ServerError> {
+        let query = query.to_lowercase();
+        let users = self.users.read().unwrap();
+        let mut vec_user = users
+            .values()
+            .filter(|user| user.user_name.to_lowercase().contains(&query))
+            .cloned()
+            .collect::<Vec<UserData>>();
+        vec_user.sort_by(|a, b| a.user_name.cmp(&b.user_name));
+        vec_user.truncate(limit);
+        Ok(vec_user)
+    }
+
+    async fn add_channel(&self, user_uuid: &str, room_uuid: &str) -> Result<(), ServerError> {
+        let mut users = self.users.write().unwrap();
+        if let Some(user_data) = users.get_mut(user_uuid) {
+            if user_data
+                .joined_channels
+                .iter()
+                .any(|uuid| uuid == room_uuid)
+            {
+                return Err(ServerError::AddChannelError);
+            }
+            user_data.joined_channels.push(room_uuid.to_string());
+        }
+        Ok(())
+    }
+
+    async fn remove_channel(&self, user_uuid: &str, room_uuid: &str) -> Result<(), ServerError> {
+        let mut users = self.users.write().unwrap();
+        if let Some(user_data) = users.get_mut(user_uuid) {
+            if !user_data
+                .joined_channels
+                .iter()
+                .any(|uuid| uuid == room_uuid)
+            {
+                return Err(ServerError::RemoveChannelError);
+            }
+            user_data.joined_channels.retain(|uuid| uuid != room_uuid);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl RoomRepository for MemoryRepository {
+    async fn insert_room(&self, room_data: &RoomData) -> Result<(), ServerError> {
+        let mut rooms = self.rooms.write().unwrap();
+        // same as a surreal create, an existing room is never replaced
+        if rooms.contains_key(&room_data.room_uuid) {
+            return Err(ServerError::RoomExists);
+        }
+        rooms.insert(room_data.room_uuid.clone(), room_data.clone());
+        Ok(())
+    }
+
+    async fn get_room(&self, room_uuid: &str) -> Result<Option<RoomData>, ServerError> {
+        let rooms = self.rooms.read().unwrap();
+        Ok(rooms.get(room_uuid).cloned())
+    }
+
+    async fn update_room(&self, room_data: &RoomData) -> Result<bool, ServerError> {
+        let mut rooms = self.rooms.write().unwrap();
+        match rooms.get_mut(&room_data.room_uuid) {
+            Some(entry) if entry.version == room_data.version => {
+                *entry = RoomData {
+                    version: room_data.version + 1,
+                    ..room_data.clone()
+                };
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn delete_room(&self, room_uuid: &str) -> Result<(), ServerError> {
+        let mut rooms = self.rooms.write().unwrap();
+        rooms.remove(room_uuid);
+        self.invites
+            .write()
+            .unwrap()
+            .retain(|_, invite| invite.room_uuid != room_uuid);
+        self.read_markers
+            .write()
+            .unwrap()
+            .retain(|_, read_marker| read_marker.room_uuid != room_uuid);
+        Ok(())
+    }
+
+    async fn search_public_rooms(
+        &self,
+        query: &str,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<RoomData>, ServerError> {
+        let rooms = self.rooms.read().unwrap();
+        let query = query.to_lowercase();
+        let mut vec_room = rooms
+            .values()
+            .filter(|room| room.public && !room.archived && room.kind == RoomKind::Channel)
+            .filter(|room| {
+                room.room_name.to_lowercase().contains(&query)
+                    || room.description.to_lowercase().contains(&query)
+            })
+            .cloned()
+            .collect::<Vec<RoomData>>();
+        vec_room.sort_by(|a, b| a.room_name.cmp(&b.room_name));
+        Ok(vec_room.into_iter().skip(start).take(limit).collect())
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl MessageRepository for MemoryRepository {
+    async fn insert_msg(&self, msg_data: &MsgData) -> Result<(), ServerError> {
+        let mut messages = self.messages.write().unwrap();
+        messages.insert(msg_data.msg_uuid.clone(), msg_data.clone());
+        Ok(())
+    }
+
+    async fn get_msg(&self, msg_uuid: &str) -> Result<Option<MsgData>, ServerError> {
+        let messages = self.messages.read().unwrap();
+        Ok(messages.get(msg_uuid).cloned())
+    }
+
+    async fn update_msg(&self, msg_data: &MsgData) -> Result<(), ServerError> {
+        let mut messages = self.messages.write().unwrap();
+        if let Some(entry) = messages.get_mut(&msg_data.msg_uuid) {
+            *entry = msg_data.clone();
+        }
+        Ok(())
+    }
+
+    async fn tombstone_msg(&self, msg_data: &MsgData) -> Result<(), ServerError> {
+        self.update_msg(msg_data).await?;
+        self.revisions
+            .write()
+            .unwrap()
+            .retain(|revision| revision.msg_uuid != msg_data.msg_uuid);
+        self.reactions
+            .write()
+            .unwrap()
+            .retain(|reaction| reaction.msg_uuid != msg_data.msg_uuid);
+        Ok(())
+    }
+
+    async fn insert_revision(&self, revision: &MsgRevision) -> Result<(), ServerError> {
+        self.revisions.write().unwrap().push(revision.clone());
+        Ok(())
+    }
+
+    async fn get_revisions(&self, msg_uuid: &str) -> Result<Vec<MsgRevision>, ServerError> {
+        let revisions = self.revisions.read().unwrap();
+        let mut vec_revision = revisions
+            .iter()
+            .filter(|revision| revision.msg_uuid == msg_uuid)
+            .cloned()
+            .collect::<Vec<MsgRevision>>();
+        vec_revision.sort_by_key(|revision| revision.edited_at);
+        Ok(vec_revision)
+    }
+
+    async fn delete_room_msgs(&self, room_uuid: &str) -> Result<(), ServerError> {
+        let mut messages = self.messages.write().unwrap();
+        let msg_uuids = messages
+            .values()
+            .filter(|msg| msg.channel == room_uuid)
+            .map(|msg| msg.msg_uuid.clone())
+            .collect::<Vec<String>>();
+        messages.retain(|_, msg| msg.channel != room_uuid);
+        self.revisions
+            .write()
+            .unwrap()
+            .retain(|revision| !msg_uuids.contains(&revision.msg_uuid));
+        self.reactions
+            .write()
+            .unwrap()
+            .retain(|reaction| !msg_uuids.contains(&reaction.msg_uuid));
+        Ok(())
+    }
+
+    async fn get_msg_page(
+        &self,
+        room_uuid: &str,
+        parent: Option<&str>,
+        before: Option<&MsgCursor>,
+        after: Option<&MsgCursor>,
+        limit: usize,
+    ) -> Result<Vec<MsgData>, ServerError> {
+        let position = |msg: &MsgData| (msg.created_at, msg.msg_uuid.clone());
+        let messages = self.messages.read().unwrap();
+        let mut vec_msg = messages
+            .values()
+            .filter(|msg| msg.channel == room_uuid && msg.parent.as_deref() == parent)
+            .cloned()
+            .filter(|msg| match before {
+                Some(cursor) => position(msg) < (cursor.created_at, cursor.msg_uuid.clone()),
+                None => true,
+            })
+            .filter(|msg| match after {
+                Some(cursor) => position(msg) > (cursor.created_at, cursor.msg_uuid.clone()),
+                None => true,
+            })
+            .collect::<Vec<MsgData>>();
+        vec_msg.sort_by_key(position);
+        if after.is_none() {
+            vec_msg.reverse();
+        }
+        vec_msg.truncate(limit);
+        Ok(vec_msg)
+    }
+
+    async fn count_unread(
+        &self,
+        room_uuid: &str,
+        user_uuid: &str,
+        after: Option<&MsgCursor>,
+    ) -> Result<UnreadCount, ServerError> {
+        let messages = self.messages.read().unwrap();
+        let vec_unread = messages
+            .values()
+            .filter(|msg| msg.channel == room_uuid && msg.parent.is_none())
+            .filter(|msg| !msg.is_deleted() && msg.msg_sender != user_uuid)
+            .filter(|msg| match after {
+                Some(cursor) => {
+                    (msg.created_at, &msg.msg_uuid) > (cursor.created_at, &cursor.msg_uuid)
+                }
+                None => true,
+            })
+            .collect::<Vec<&MsgData>>();
+        Ok(UnreadCount {
+            unread: vec_unread.len(),
+            mentions: vec_unread
+                .iter()
+                .filter(|msg| {
+                    msg.mentions
+                        .iter()
+                        .any(|mention| mention.user_uuid == user_uuid)
+                })
+                .count(),
+        })
+    }
+
+    async fn count_replies(
+        &self,
+        msg_uuids: &[String],
+    ) -> Result<HashMap<String, usize>, ServerError> {
+        let messages = self.messages.read().unwrap();
+        let mut reply_counts = HashMap::<String, usize>::new();
+        for parent in messages.values().filter_map(|msg| msg.parent.as_ref()) {
+            if msg_uuids.contains(parent) {
+                *reply_counts.entry(parent.clone()).or_default() += 1;
+            }
+        }
+        Ok(reply_counts)
+    }
+
+    async fn last_activity(
+        &self,
+        room_uuids: &[String],
+    ) -> Result<HashMap<String, DateTime<Utc>>, ServerError> {
+        let messages = self.messages.read().unwrap();
+        let mut last_activity = HashMap::<String, DateTime<Utc>>::new();
+        for msg in messages
+            .values()
+            .filter(|msg| room_uuids.contains(&msg.channel))
+        {
+            let last_at = last_activity
+                .entry(msg.channel.clone())
+                .or_insert(msg.created_at);
+            *last_at = (*last_at).max(msg.created_at);
+        }
+        Ok(last_activity)
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl ReactionRepository for MemoryRepository {
+    async fn insert_reaction(&self, reaction: &ReactionData) -> Result<(), ServerError> {
+        let mut reactions = self.reactions.write().unwrap();
+        let exists = reactions.iter().any(|known| {
+            known.msg_uuid == reaction.msg_uuid
+                && known.user_uuid == reaction.user_uuid
+                && known.emoji == reaction.emoji
+        });
+        if exists {
+            return Err(ServerError::ReactionExists);
+        }
+        reactions.push(reaction.clone());
+        Ok(())
+    }
+
+    async fn delete_reaction(
+        &self,
+        msg_uuid: &str,
+        user_uuid: &str,
+        emoji: &str,
+    ) -> Result<(), ServerError> {
+        self.reactions.write().unwrap().retain(|reaction| {
+            !(reaction.msg_uuid == msg_uuid
+                && reaction.user_uuid == user_uuid
+                && reaction.emoji == emoji)
+        });
+        Ok(())
+    }
+
+    async fn get_reactions(&self, msg_uuids: &[String]) -> Result<Vec<ReactionData>, ServerError> {
+        let reactions = self.reactions.read().unwrap();
+        Ok(reactions
+            .iter()
+            .filter(|reaction| msg_uuids.contains(&reaction.msg_uuid))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl ReadMarkerRepository for MemoryRepository {
+    async fn get_read_marker(
+        &self,
+        user_uuid: &str,
+        room_uuid: &str,
+    ) -> Result<Option<ReadMarker>, ServerError> {
+        let read_markers = self.read_markers.read().unwrap();
+        Ok(read_markers
+            .get(&format!("{}_{}", user_uuid, room_uuid))
+            .cloned())
+    }
+
+    async fn get_room_read_markers(&self, room_uuid: &str) -> Result<Vec<ReadMarker>, ServerError> {
+        let read_markers = self.read_markers.read().unwrap();
+        Ok(read_markers
+            .values()
+            .filter(|read_marker| read_marker.room_uuid == room_uuid)
+            .cloned()
+            .collect())
+    }
+
+    async fn upsert_read_marker(&self, read_marker: &ReadMarker) -> Result<(), ServerError> {
+        let mut read_markers = self.read_markers.write().unwrap();
+        read_markers.insert(read_marker.id(), read_marker.clone());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl InviteRepository for MemoryRepository {
+    async fn insert_invite(&self, invite: &Invite) -> Result<(), ServerError> {
+        let mut invites = self.invites.write().unwrap();
+        invites.insert(invite.code.clone(), invite.clone());
+        Ok(())
+    }
+
+    async fn get_invite(&self, code: &str) -> Result<Option<Invite>, ServerError> {
+        let invites = self.invites.read().unwrap();
+        Ok(invites.get(code).cloned())
+    }
+
+    async fn get_room_invites(&self, room_uuid: &str) -> Result<Vec<Invite>, ServerError> {
+        let invites = self.invites.read().unwrap();
+        let mut vec_invite = invites
+            .values()
+            .filter(|invite| invite.room_uuid == room_uuid)
+            .cloned()
+            .collect::<Vec<Invite>>();
+        vec_invite.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(vec_invite)
+    }
+
+    async fn redeem_invite(&self, code: &str, now: DateTime<Utc>) -> Result<bool, ServerError> {
+        let mut invites = self.invites.write().unwrap();
+        match invites.get_mut(code) {
+            Some(invite) if invite.is_usable(now) => {
+                invite.uses += 1;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn revoke_invite(
+        &self,
+        code: &str,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<(), ServerError> {
+        let mut invites = self.invites.write().unwrap();
+        if let Some(invite) = invites.get_mut(code) {
+            invite.revoked_at.get_or_insert(revoked_at);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use {
+        crate::{
+            error::ServerError,
+            models::{
+                message_model::{MsgCursor, MsgData, MsgResponse},
+                reaction_model::ReactionData,
+            },
+            state::{repository::Repository, rooms_manager::RoomData},
+        },
+        chrono::{Duration, Utc},
+    };
+
+    // five messages, the last three sent within the same instant
+    async fn seed_messages(repository: &Repository, room_uuid: &str) -> Vec<MsgData> {
+        let now = Utc::now();
+        let sent_at = [
+            now - Duration::seconds(2),
+            now - Duration::seconds(1),
+            now,
+            now,
+            now,
+        ];
+        let mut vec_msg = Vec::new();
+        for (idx, created_at) in sent_at.into_iter().enumerate() {
+            let msg_data = MsgData::new(
+                room_uuid.to_string(),
+                "sender".to_string(),
+                format!("message {}", idx),
+                None,
+                created_at,
+            );
+            repository.messages.insert_msg(&msg_data).await.unwrap();
+            vec_msg.push(msg_data);
+        }
+        vec_msg.sort_by(|a, b| (a.created_at, &a.msg_uuid).cmp(&(b.created_at, &b.msg_uuid)));
+        vec_msg
+    }
+
+    fn cursor(msg_data: &MsgData) -> MsgCursor {
+        MsgCursor {
+            created_at: msg_data.created_at,
+            msg_uuid: msg_data.msg_uuid.clone(),
+        }
+    }
+
+    #[tokio::test]
+    async fn pages_walk_back_through_equal_timestamps() {
+        let repository = Repository::memory();
+        let vec_msg = seed_messages(&repository, "room").await;
+
+        let mut seen = Vec::new();
+        let mut before = None;
+        loop {
+            let page = MsgResponse::get_page("room", None, before.as_ref(), None, 2, &repository)
+                .await
+                .unwrap();
+            // oldest first within a page, the earlier pages go in front
+            let mut uuids = page
+                .messages
+                .iter()
+                .map(|msg| msg.msg_uuid.clone())
+                .collect::<Vec<String>>();
+            uuids.append(&mut seen);
+            seen = uuids;
+            before = page.messages.first().map(MsgCursor::from_msg);
+            if !page.has_more {
+                break;
+            }
+        }
+        let expected = vec_msg
+            .iter()
+            .map(|msg| msg.msg_uuid.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(seen, expected);
+    }
+
+    #[tokio::test]
+    async fn pages_walk_forward_from_a_cursor() {
+        let repository = Repository::memory();
+        let vec_msg = seed_messages(&repository, "room").await;
+
+        let after = cursor(&vec_msg[2]);
+        let page = repository
+            .messages
+            .get_msg_page("room", None, None, Some(&after), 10)
+            .await
+            .unwrap();
+        let uuids = page
+            .iter()
+            .map(|msg| msg.msg_uuid.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            uuids,
+            [vec_msg[3].msg_uuid.as_str(), vec_msg[4].msg_uuid.as_str()]
+        );
+    }
+
+    #[tokio::test]
+    async fn replies_are_paged_apart_from_the_room() {
+        let repository = Repository::memory();
+        let vec_msg = seed_messages(&repository, "room").await;
+        let parent = &vec_msg[0];
+        let reply = MsgData::new(
+            "room".to_string(),
+            "sender".to_string(),
+            "reply".to_string(),
+            Some(parent.msg_uuid.clone()),
+            Utc::now(),
+        );
+        repository.messages.insert_msg(&reply).await.unwrap();
+
+        let top_level = repository
+            .messages
+            .get_msg_page("room", None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(top_level.len(), vec_msg.len());
+        let thread = repository
+            .messages
+            .get_msg_page("room", Some(parent.msg_uuid.as_str()), None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(thread.len(), 1);
+        assert_eq!(thread[0].msg_uuid, reply.msg_uuid);
+    }
+
+    #[tokio::test]
+    async fn duplicate_reactions_are_refused() {
+        let repository = Repository::memory();
+        let reaction = ReactionData::new(
+            "msg".to_string(),
+            "user".to_string(),
+            "👍".to_string(),
+            Utc::now(),
+        );
+        repository
+            .reactions
+            .insert_reaction(&reaction)
+            .await
+            .unwrap();
+        let duplicate = repository.reactions.insert_reaction(&reaction).await;
+        assert!(matches!(duplicate, Err(ServerError::ReactionExists)));
+    }
+
+    #[tokio::test]
+    async fn the_directory_is_paged_by_name() {
+        let repository = Repository::memory();
+        for room_name in ["delta", "alpha", "charlie", "bravo"] {
+            let mut room_data =
+                RoomData::new(room_name.to_string(), "owner".to_string(), Utc::now());
+            room_data.public = true;
+            repository.rooms.insert_room(&room_data).await.unwrap();
+        }
+        let private = RoomData::new("echo".to_string(), "owner".to_string(), Utc::now());
+        repository.rooms.insert_room(&private).await.unwrap();
+
+        let names = |vec_room: Vec<RoomData>| {
+            vec_room
+                .into_iter()
+                .map(|room_data| room_data.room_name)
+                .collect::<Vec<String>>()
+        };
+        let first = repository
+            .rooms
+            .search_public_rooms("", 0, 2)
+            .await
+            .unwrap();
+        assert_eq!(names(first), ["alpha", "bravo"]);
+        let second = repository
+            .rooms
+            .search_public_rooms("", 2, 2)
+            .await
+            .unwrap();
+        assert_eq!(names(second), ["charlie", "delta"]);
+        let searched = repository
+            .rooms
+            .search_public_rooms("CHAR", 0, 2)
+            .await
+            .unwrap();
+        assert_eq!(names(searched), ["charlie"]);
+    }
+
+    #[tokio::test]
+    async fn rooms_are_never_inserted_twice() {
+        let repository = Repository::memory();
+        let room_data = RoomData::new("general".to_string(), "owner".to_string(), Utc::now());
+        repository.rooms.insert_room(&room_data).await.unwrap();
+        let mut renamed = room_data.clone();
+        renamed.room_name = "random".to_string();
+        let duplicate = repository.rooms.insert_room(&renamed).await;
+        assert!(matches!(duplicate, Err(ServerError::RoomExists)));
+        let stored = repository
+            .rooms
+            .get_room(&room_data.room_uuid)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.room_name, "general");
+    }
+}
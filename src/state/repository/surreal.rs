@@ -0,0 +1,731 @@
+#[cfg(feature = "ssr")]
+use {
+    super::{
+        InviteRepository, MessageRepository, ReactionRepository, ReadMarkerRepository,
+        RoomRepository, UserRepository,
+    },
+    crate::{
+        error::ServerError,
+        models::{
+            invite_model::Invite,
+            message_model::{MsgCursor, MsgData, MsgRevision},
+            reaction_model::ReactionData,
+            read_marker_model::{ReadMarker, UnreadCount},
+            user_model::UserData,
+        },
+        state::{db::Database, rooms_manager::RoomData},
+    },
+    async_trait::async_trait,
+    chrono::{DateTime, Utc},
+    serde::Deserialize,
+    std::collections::HashMap,
+};
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl UserRepository for Database {
+    async fn insert_user(&self, user_data: &UserData) -> Result<(), ServerError> {
+        // looked up first instead of reading the wording of the index violation,
+        // `user_email_idx` (migrations/0001_initial_schema.surql) still rejects two
+        // registrations racing for the same email
+        if self.get_user_by_email(&user_data.email).await?.is_some() {
+            return Err(ServerError::EmailTaken);
+        }
+        self.client
+            .create::<Option<UserData>>(("user_data", &user_data.uuid))
+            .content(user_data)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_user_by_uuid(&self, uuid: &str) -> Result<Option<UserData>, ServerError> {
+        let mut query_result = self
+            .client
+            .query("SELECT * FROM type::table($table) WHERE uuid = $uuid")
+            .bind(("table", "user_data"))
+            .bind(("uuid", uuid))
+            .await?;
+        Ok(query_result.take::<Option<UserData>>(0)?)
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> Result<Option<UserData>, ServerError> {
+        let mut query_result = self
+            .client
+            .query("SELECT * FROM type::table($table) WHERE email = $email")
+            .bind(("table", "user_data"))
+            .bind(("email", email))
+            .await?;
+        Ok(query_result.take::<Option<UserData>>(0)?)
+    }
+
+    async fn search_users(&self, query: &str, limit: usize) -> Result<Vec<UserData>, ServerError> {
+        let mut query_result = self
+            .client
+            .query(
+                "SELECT * FROM user_data WHERE string::contains(string::lowercase(user_name), $query)
+                    ORDER BY user_name ASC LIMIT $limit",
+            )
+            .bind(("query", query.to_lowercase()))
+            .bind(("limit", limit))
+            .await?;
+        Ok(query_result.take::<Vec<UserData>>(0)?)
+    }
+
+    async fn add_channel(&self, user_uuid: &str, room_uuid: &str) -> Result<(), ServerError> {
+        let find_entry = self
+            .client
+            .select::<Option<UserData>>(("user_data", user_uuid))
+            .await?;
+
+        if let Some(user_data) = find_entry {
+            if user_data
+                .joined_channels
+                .iter()
+                .any(|uuid| uuid == room_uuid)
+            {
+                return Err(ServerError::AddChannelError);
+            }
+
+            self.client
+                .update::<Option<UserData>>(("user_data", user_uuid))
+                .patch(surrealdb::opt::PatchOp::add("/joined_channels", room_uuid))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn remove_channel(&self, user_uuid: &str, room_uuid: &str) -> Result<(), ServerError> {
+        let find_entry = self
+            .client
+            .select::<Option<UserData>>(("user_data", user_uuid))
+            .await?;
+
+        if let Some(mut user_data) = find_entry {
+            if !user_data
+                .joined_channels
+                .iter()
+                .any(|uuid| uuid == room_uuid)
+            {
+                return Err(ServerError::RemoveChannelError);
+            }
+            user_data.joined_channels.retain(|uuid| uuid != room_uuid);
+
+            self.client
+                .update::<Option<UserData>>(("user_data", user_uuid))
+                .merge(user_data)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl RoomRepository for Database {
+    async fn insert_room(&self, room_data: &RoomData) -> Result<(), ServerError> {
+        self.client
+            .create::<Option<RoomData>>(("room_data", &room_data.room_uuid))
+            .content(room_data)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_room(&self, room_uuid: &str) -> Result<Option<RoomData>, ServerError> {
+        Ok(self
+            .client
+            .select::<Option<RoomData>>(("room_data", room_uuid))
+            .await?)
+    }
+
+    async fn update_room(&self, room_data: &RoomData) -> Result<bool, ServerError> {
+        // replaced as a whole, merging would keep the members and roles that were removed
+        let updated = RoomData {
+            version: room_data.version + 1,
+            ..room_data.clone()
+        };
+        let mut query_result = self
+            .client
+            .query("UPDATE type::thing('room_data', $room_uuid) CONTENT $room_data WHERE version = $version")
+            .bind(("room_uuid", &room_data.room_uuid))
+            .bind(("room_data", updated))
+            .bind(("version", room_data.version))
+            .await?;
+        Ok(!query_result.take::<Vec<RoomData>>(0)?.is_empty())
+    }
+
+    async fn delete_room(&self, room_uuid: &str) -> Result<(), ServerError> {
+        self.client
+            .query("BEGIN TRANSACTION")
+            .query("DELETE invite WHERE room_uuid = $room_uuid")
+            .query("DELETE read_marker WHERE room_uuid = $room_uuid")
+            .query("DELETE type::thing('room_data', $room_uuid)")
+            .query("COMMIT TRANSACTION")
+            .bind(("room_uuid", room_uuid))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    async fn search_public_rooms(
+        &self,
+        query: &str,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<RoomData>, ServerError> {
+        let mut query_result = self
+            .client
+            .query(
+                "SELECT * FROM room_data WHERE public = true AND archived = false AND kind = 'channel'
+                    AND (string::contains(string::lowercase(room_name), $query)
+                        OR string::contains(string::lowercase(description), $query))
+                    ORDER BY room_name ASC LIMIT $limit START $start",
+            )
+            .bind(("query", query.to_lowercase()))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await?;
+        Ok(query_result.take::<Vec<RoomData>>(0)?)
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl MessageRepository for Database {
+    async fn insert_msg(&self, msg_data: &MsgData) -> Result<(), ServerError> {
+        self.client
+            .create::<Option<MsgData>>(("message", &msg_data.msg_uuid))
+            .content(msg_data)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_msg(&self, msg_uuid: &str) -> Result<Option<MsgData>, ServerError> {
+        Ok(self
+            .client
+            .select::<Option<MsgData>>(("message", msg_uuid))
+            .await?)
+    }
+
+    async fn update_msg(&self, msg_data: &MsgData) -> Result<(), ServerError> {
+        self.client
+            .update::<Option<MsgData>>(("message", &msg_data.msg_uuid))
+            .merge(msg_data)
+            .await?;
+        Ok(())
+    }
+
+    async fn tombstone_msg(&self, msg_data: &MsgData) -> Result<(), ServerError> {
+        self.client
+            .query("BEGIN TRANSACTION")
+            .query("UPDATE type::thing('message', $msg_uuid) MERGE $msg_data")
+            .query("DELETE message_revision WHERE msg_uuid = $msg_uuid")
+            .query("DELETE reaction WHERE msg_uuid = $msg_uuid")
+            .query("COMMIT TRANSACTION")
+            .bind(("msg_uuid", &msg_data.msg_uuid))
+            .bind(("msg_data", msg_data))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    async fn insert_revision(&self, revision: &MsgRevision) -> Result<(), ServerError> {
+        self.client
+            .create::<Vec<MsgRevision>>("message_revision")
+            .content(revision)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_revisions(&self, msg_uuid: &str) -> Result<Vec<MsgRevision>, ServerError> {
+        let mut query_result = self
+            .client
+            .query(
+                "SELECT * FROM message_revision WHERE msg_uuid = $msg_uuid ORDER BY edited_at ASC",
+            )
+            .bind(("msg_uuid", msg_uuid))
+            .await?;
+        Ok(query_result.take::<Vec<MsgRevision>>(0)?)
+    }
+
+    async fn delete_room_msgs(&self, room_uuid: &str) -> Result<(), ServerError> {
+        self.client
+            .query("BEGIN TRANSACTION")
+            .query("LET $msg_uuids = (SELECT VALUE msg_uuid FROM message WHERE channel = $channel)")
+            .query("DELETE message_revision WHERE msg_uuid INSIDE $msg_uuids")
+            .query("DELETE reaction WHERE msg_uuid INSIDE $msg_uuids")
+            .query("DELETE message WHERE channel = $channel")
+            .query("COMMIT TRANSACTION")
+            .bind(("channel", room_uuid))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    async fn get_msg_page(
+        &self,
+        room_uuid: &str,
+        parent: Option<&str>,
+        before: Option<&MsgCursor>,
+        after: Option<&MsgCursor>,
+        limit: usize,
+    ) -> Result<Vec<MsgData>, ServerError> {
+        let mut sql = String::from("SELECT * FROM message WHERE channel = $channel");
+        if parent.is_some() {
+            sql.push_str(" AND parent = $parent");
+        } else {
+            sql.push_str(" AND parent IS NONE");
+        }
+        if before.is_some() {
+            sql.push_str(
+                " AND (created_at < <datetime> $before_at
+                    OR (created_at = <datetime> $before_at AND msg_uuid < $before_uuid))",
+            );
+        }
+        if after.is_some() {
+            sql.push_str(
+                " AND (created_at > <datetime> $after_at
+                    OR (created_at = <datetime> $after_at AND msg_uuid > $after_uuid))",
+            );
+        }
+        if after.is_some() {
+            sql.push_str(" ORDER BY created_at ASC, msg_uuid ASC LIMIT $limit");
+        } else {
+            sql.push_str(" ORDER BY created_at DESC, msg_uuid DESC LIMIT $limit");
+        }
+
+        let mut query = self
+            .client
+            .query(sql)
+            .bind(("channel", room_uuid))
+            .bind(("parent", parent))
+            .bind(("limit", limit));
+        if let Some(cursor) = before {
+            query = query
+                .bind(("before_at", cursor.created_at))
+                .bind(("before_uuid", &cursor.msg_uuid));
+        }
+        if let Some(cursor) = after {
+            query = query
+                .bind(("after_at", cursor.created_at))
+                .bind(("after_uuid", &cursor.msg_uuid));
+        }
+        let mut query_result = query.await?;
+        Ok(query_result.take::<Vec<MsgData>>(0)?)
+    }
+
+    async fn count_unread(
+        &self,
+        room_uuid: &str,
+        user_uuid: &str,
+        after: Option<&MsgCursor>,
+    ) -> Result<UnreadCount, ServerError> {
+        let mut sql = String::from(
+            "SELECT count() AS unread, count(mentions.*.user_uuid CONTAINS $user_uuid) AS mentions
+                FROM message WHERE channel = $channel AND parent IS NONE
+                AND deleted_at IS NONE AND msg_sender != $user_uuid",
+        );
+        if after.is_some() {
+            sql.push_str(
+                " AND (created_at > <datetime> $after_at
+                    OR (created_at = <datetime> $after_at AND msg_uuid > $after_uuid))",
+            );
+        }
+        sql.push_str(" GROUP ALL");
+
+        let mut query = self
+            .client
+            .query(sql)
+            .bind(("channel", room_uuid))
+            .bind(("user_uuid", user_uuid));
+        if let Some(cursor) = after {
+            query = query
+                .bind(("after_at", cursor.created_at))
+                .bind(("after_uuid", &cursor.msg_uuid));
+        }
+        let mut query_result = query.await?;
+        Ok(query_result
+            .take::<Option<UnreadCount>>(0)?
+            .unwrap_or_default())
+    }
+
+    async fn count_replies(
+        &self,
+        msg_uuids: &[String],
+    ) -> Result<HashMap<String, usize>, ServerError> {
+        #[derive(Deserialize)]
+        struct ReplyCount {
+            parent: String,
+            replies: usize,
+        }
+
+        let mut query_result = self
+            .client
+            .query("SELECT parent, count() AS replies FROM message WHERE parent INSIDE $msg_uuids GROUP BY parent")
+            .bind(("msg_uuids", msg_uuids))
+            .await?;
+        Ok(query_result
+            .take::<Vec<ReplyCount>>(0)?
+            .into_iter()
+            .map(|reply_count| (reply_count.parent, reply_count.replies))
+            .collect())
+    }
+
+    async fn last_activity(
+        &self,
+        room_uuids: &[String],
+    ) -> Result<HashMap<String, DateTime<Utc>>, ServerError> {
+        #[derive(Deserialize)]
+        struct LastActivity {
+            channel: String,
+            last_at: DateTime<Utc>,
+        }
+
+        let mut query_result = self
+            .client
+            .query("SELECT channel, math::max(created_at) AS last_at FROM message WHERE channel INSIDE $room_uuids GROUP BY channel")
+            .bind(("room_uuids", room_uuids))
+            .await?;
+        Ok(query_result
+            .take::<Vec<LastActivity>>(0)?
+            .into_iter()
+            .map(|last_activity| (last_activity.channel, last_activity.last_at))
+            .collect())
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl ReactionRepository for Database {
+    async fn insert_reaction(&self, reaction: &ReactionData) -> Result<(), ServerError> {
+        // looked up first so both backends answer a duplicate the same way,
+        // `reaction_unique_idx` (migrations/0004_reactions.surql) still catches races
+        let mut query_result = self
+            .client
+            .query("SELECT * FROM reaction WHERE msg_uuid = $msg_uuid AND user_uuid = $user_uuid AND emoji = $emoji")
+            .bind(("msg_uuid", &reaction.msg_uuid))
+            .bind(("user_uuid", &reaction.user_uuid))
+            .bind(("emoji", &reaction.emoji))
+            .await?;
+        if query_result.take::<Option<ReactionData>>(0)?.is_some() {
+            return Err(ServerError::ReactionExists);
+        }
+        self.client
+            .create::<Vec<ReactionData>>("reaction")
+            .content(reaction)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_reaction(
+        &self,
+        msg_uuid: &str,
+        user_uuid: &str,
+        emoji: &str,
+    ) -> Result<(), ServerError> {
+        self.client
+            .query("DELETE reaction WHERE msg_uuid = $msg_uuid AND user_uuid = $user_uuid AND emoji = $emoji")
+            .bind(("msg_uuid", msg_uuid))
+            .bind(("user_uuid", user_uuid))
+            .bind(("emoji", emoji))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    async fn get_reactions(&self, msg_uuids: &[String]) -> Result<Vec<ReactionData>, ServerError> {
+        let mut query_result = self
+            .client
+            .query(
+                "SELECT * FROM reaction WHERE msg_uuid INSIDE $msg_uuids ORDER BY created_at ASC",
+            )
+            .bind(("msg_uuids", msg_uuids))
+            .await?;
+        Ok(query_result.take::<Vec<ReactionData>>(0)?)
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl ReadMarkerRepository for Database {
+    async fn get_read_marker(
+        &self,
+        user_uuid: &str,
+        room_uuid: &str,
+    ) -> Result<Option<ReadMarker>, ServerError> {
+        let mut query_result = self
+            .client
+            .query(
+                "SELECT * FROM read_marker WHERE user_uuid = $user_uuid AND room_uuid = $room_uuid",
+            )
+            .bind(("user_uuid", user_uuid))
+            .bind(("room_uuid", room_uuid))
+            .await?;
+        Ok(query_result.take::<Option<ReadMarker>>(0)?)
+    }
+
+    async fn get_room_read_markers(&self, room_uuid: &str) -> Result<Vec<ReadMarker>, ServerError> {
+        let mut query_result = self
+            .client
+            .query("SELECT * FROM read_marker WHERE room_uuid = $room_uuid")
+            .bind(("room_uuid", room_uuid))
+            .await?;
+        Ok(query_result.take::<Vec<ReadMarker>>(0)?)
+    }
+
+    async fn upsert_read_marker(&self, read_marker: &ReadMarker) -> Result<(), ServerError> {
+        // updating a record that doesn't exist yet creates it
+        self.client
+            .update::<Option<ReadMarker>>(("read_marker", read_marker.id()))
+            .content(read_marker)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait]
+impl InviteRepository for Database {
+    async fn insert_invite(&self, invite: &Invite) -> Result<(), ServerError> {
+        // codes are unique through `invite_code_idx`, see migrations/0013_invites.surql
+        self.client
+            .create::<Option<Invite>>(("invite", &invite.code))
+            .content(invite)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_invite(&self, code: &str) -> Result<Option<Invite>, ServerError> {
+        let mut query_result = self
+            .client
+            .query("SELECT * FROM invite WHERE code = $code")
+            .bind(("code", code))
+            .await?;
+        Ok(query_result.take::<Option<Invite>>(0)?)
+    }
+
+    async fn get_room_invites(&self, room_uuid: &str) -> Result<Vec<Invite>, ServerError> {
+        let mut query_result = self
+            .client
+            .query("SELECT * FROM invite WHERE room_uuid = $room_uuid ORDER BY created_at DESC")
+            .bind(("room_uuid", room_uuid))
+            .await?;
+        Ok(query_result.take::<Vec<Invite>>(0)?)
+    }
+
+    async fn redeem_invite(&self, code: &str, now: DateTime<Utc>) -> Result<bool, ServerError> {
+        // the conditions mirror `Invite::is_usable`
+        let mut query_result = self
+            .client
+            .query(
+                "UPDATE invite SET uses += 1 WHERE code = $code
+                    AND revoked_at IS NONE
+                    AND (expires_at IS NONE OR expires_at > <datetime> $now)
+                    AND (max_uses IS NONE OR uses < max_uses)",
+            )
+            .bind(("code", code))
+            .bind(("now", now))
+            .await?;
+        Ok(!query_result.take::<Vec<Invite>>(0)?.is_empty())
+    }
+
+    async fn revoke_invite(
+        &self,
+        code: &str,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<(), ServerError> {
+        self.client
+            .query("UPDATE invite SET revoked_at = <datetime> $revoked_at WHERE code = $code AND revoked_at IS NONE")
+            .bind(("code", code))
+            .bind(("revoked_at", revoked_at))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use {
+        super::*,
+        crate::{models::mention_model::Mention, state::migrations},
+    };
+
+    // a fresh in memory database for every test, migrated the way the server does at startup
+    async fn database() -> Database {
+        let pool = Database::memory().await.unwrap();
+        migrations::run(&pool).await.unwrap();
+        pool
+    }
+
+    fn new_user(uuid: &str, email: &str) -> UserData {
+        UserData::new(
+            uuid.to_string(),
+            "alice".to_string(),
+            email.to_string(),
+            String::new(),
+            Utc::now(),
+        )
+    }
+
+    #[tokio::test]
+    async fn users_round_trip_through_the_memory_database() {
+        let pool = database().await;
+        pool.insert_user(&new_user("alice", "alice@example.com"))
+            .await
+            .unwrap();
+        let found = pool
+            .get_user_by_email("alice@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.uuid, "alice");
+
+        let taken = pool
+            .insert_user(&new_user("other", "alice@example.com"))
+            .await;
+        assert!(matches!(taken, Err(ServerError::EmailTaken)));
+    }
+
+    #[tokio::test]
+    async fn pages_walk_back_through_equal_timestamps() {
+        let pool = database().await;
+        let now = Utc::now();
+        let mut vec_msg = Vec::new();
+        for idx in 0..4 {
+            let msg_data = MsgData::new(
+                "room".to_string(),
+                "sender".to_string(),
+                format!("message {}", idx),
+                None,
+                now,
+            );
+            pool.insert_msg(&msg_data).await.unwrap();
+            vec_msg.push(msg_data);
+        }
+        vec_msg.sort_by(|a, b| a.msg_uuid.cmp(&b.msg_uuid));
+
+        let latest = pool
+            .get_msg_page("room", None, None, None, 2)
+            .await
+            .unwrap();
+        let latest = latest
+            .iter()
+            .map(|msg| msg.msg_uuid.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            latest,
+            [vec_msg[3].msg_uuid.as_str(), vec_msg[2].msg_uuid.as_str()]
+        );
+        let before = MsgCursor {
+            created_at: now,
+            msg_uuid: vec_msg[2].msg_uuid.clone(),
+        };
+        let older = pool
+            .get_msg_page("room", None, Some(&before), None, 10)
+            .await
+            .unwrap();
+        let older = older
+            .iter()
+            .map(|msg| msg.msg_uuid.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            older,
+            [vec_msg[1].msg_uuid.as_str(), vec_msg[0].msg_uuid.as_str()]
+        );
+    }
+
+    #[tokio::test]
+    async fn unread_counts_skip_own_messages_and_replies() {
+        let pool = database().await;
+        let now = Utc::now();
+        let post = |sender: &str, parent: Option<String>, seconds: i64| {
+            MsgData::new(
+                "room".to_string(),
+                sender.to_string(),
+                "hello".to_string(),
+                parent,
+                now + chrono::Duration::seconds(seconds),
+            )
+        };
+        let read = post("bob", None, 0);
+        let mut mention = post("bob", None, 1);
+        mention.mentions = vec![Mention {
+            user_uuid: "alice".to_string(),
+            user_name: "alice".to_string(),
+        }];
+        let reply = post("bob", Some(read.msg_uuid.clone()), 2);
+        let own = post("alice", None, 3);
+        let latest = post("carol", None, 4);
+        for msg_data in [&read, &mention, &reply, &own, &latest] {
+            pool.insert_msg(msg_data).await.unwrap();
+        }
+
+        let everything = pool.count_unread("room", "alice", None).await.unwrap();
+        assert_eq!(
+            everything,
+            UnreadCount {
+                unread: 3,
+                mentions: 1
+            }
+        );
+        let after = MsgCursor {
+            created_at: mention.created_at,
+            msg_uuid: mention.msg_uuid.clone(),
+        };
+        let past_marker = pool
+            .count_unread("room", "alice", Some(&after))
+            .await
+            .unwrap();
+        assert_eq!(
+            past_marker,
+            UnreadCount {
+                unread: 1,
+                mentions: 0
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn stale_room_writes_are_refused() {
+        let pool = database().await;
+        let room_data = RoomData::new("general".to_string(), "alice".to_string(), Utc::now());
+        pool.insert_room(&room_data).await.unwrap();
+
+        let mut renamed = room_data.clone();
+        renamed.room_name = "random".to_string();
+        assert!(pool.update_room(&renamed).await.unwrap());
+        // still at the version read before the rename
+        assert!(!pool.update_room(&room_data).await.unwrap());
+        let stored = pool.get_room(&room_data.room_uuid).await.unwrap().unwrap();
+        assert_eq!(stored.room_name, "random");
+        assert_eq!(stored.version, 1);
+    }
+
+    #[tokio::test]
+    async fn invites_are_redeemed_until_they_run_out() {
+        let pool = database().await;
+        let now = Utc::now();
+        let limited = Invite::new("room".to_string(), "alice".to_string(), now, None, Some(2));
+        let expired = Invite::new(
+            "room".to_string(),
+            "alice".to_string(),
+            now,
+            Some(now - chrono::Duration::seconds(1)),
+            None,
+        );
+        let revoked = Invite::new("room".to_string(), "alice".to_string(), now, None, None);
+        for invite in [&limited, &expired, &revoked] {
+            pool.insert_invite(invite).await.unwrap();
+        }
+        pool.revoke_invite(&revoked.code, now).await.unwrap();
+
+        assert!(pool.redeem_invite(&limited.code, now).await.unwrap());
+        assert!(pool.redeem_invite(&limited.code, now).await.unwrap());
+        assert!(!pool.redeem_invite(&limited.code, now).await.unwrap());
+        assert!(!pool.redeem_invite(&expired.code, now).await.unwrap());
+        assert!(!pool.redeem_invite(&revoked.code, now).await.unwrap());
+        assert!(!pool.redeem_invite("missing", now).await.unwrap());
+        let stored = pool.get_invite(&limited.code).await.unwrap().unwrap();
+        assert_eq!(stored.uses, 2);
+    }
+}
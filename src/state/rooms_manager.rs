@@ -1,21 +1,81 @@
 use {
-    crate::{error::ServerError, models::user_model::User},
+    crate::{
+        error::ServerError,
+        models::{
+            invite_model::Invite,
+            role_model::{Permission, Role},
+            room_settings_model::RoomSettings,
+            user_model::User,
+        },
+    },
     chrono::{DateTime, Utc},
     serde::{Deserialize, Serialize},
-    std::collections::HashMap,
+    std::collections::{HashMap, HashSet},
     uuid::Uuid,
 };
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomKind {
+    #[default]
+    Channel,
+    // a conversation between exactly two users, nobody else can join it
+    Direct,
+    // a small conversation without a name, its participants bring in the others
+    Group,
+}
+
+impl RoomKind {
+    // direct and group conversations are only visible to their participants
+    pub fn is_conversation(&self) -> bool {
+        matches!(self, Self::Direct | Self::Group)
+    }
+}
+
+// participants of a group conversation, the creator included
+pub const GROUP_MIN: usize = 3;
+pub const GROUP_MAX: usize = 8;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoomData {
     pub room_name: String,
     pub room_uuid: String,
     pub users: HashMap<String, User>,
     pub created_at: DateTime<Utc>,
+    // uuid of the user who created the room, rooms created before owners existed have none
+    #[serde(default)]
+    pub owner: Option<String>,
+    // whether members can see how far the others have read
+    #[serde(default = "read_receipts_default")]
+    pub read_receipts: bool,
+    #[serde(default)]
+    pub kind: RoomKind,
+    // members without an entry are plain members, rooms from before roles
+    // existed only know their owner through `owner`
+    #[serde(default)]
+    pub roles: HashMap<String, Role>,
+    #[serde(default)]
+    pub banned: HashSet<String>,
+    // listed in the room directory, anyone can join without an invite
+    #[serde(default)]
+    pub public: bool,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub topic: String,
+    #[serde(default)]
+    pub archived: bool,
+    // bumped on every write, see `RoomsManager::modify_room`
+    #[serde(default)]
+    pub version: u64,
+}
+
+fn read_receipts_default() -> bool {
+    true
 }
 
 impl RoomData {
-    pub fn new(room_name: String, created_at: DateTime<Utc>) -> Self {
+    pub fn new(room_name: String, owner: String, created_at: DateTime<Utc>) -> Self {
         let room_uuid = Uuid::new_v4().as_simple().to_string();
         let users = HashMap::<String, User>::new();
         Self {
@@ -23,6 +83,149 @@ impl RoomData {
             room_uuid,
             users,
             created_at,
+            owner: Some(owner),
+            read_receipts: read_receipts_default(),
+            kind: RoomKind::Channel,
+            roles: HashMap::new(),
+            banned: HashSet::new(),
+            public: false,
+            description: String::new(),
+            topic: String::new(),
+            archived: false,
+            version: 0,
+        }
+    }
+
+    pub fn new_direct(user: User, other: User, created_at: DateTime<Utc>) -> Self {
+        let room_uuid = Self::direct_uuid(&user.uuid, &other.uuid);
+        let users = HashMap::from([(user.uuid.clone(), user), (other.uuid.clone(), other)]);
+        Self {
+            room_name: String::new(),
+            room_uuid,
+            users,
+            created_at,
+            owner: None,
+            read_receipts: read_receipts_default(),
+            kind: RoomKind::Direct,
+            roles: HashMap::new(),
+            banned: HashSet::new(),
+            public: false,
+            description: String::new(),
+            topic: String::new(),
+            archived: false,
+            version: 0,
+        }
+    }
+
+    pub fn new_group(users: Vec<User>, created_at: DateTime<Utc>) -> Self {
+        let room_uuid = Uuid::new_v4().as_simple().to_string();
+        let users = users
+            .into_iter()
+            .map(|user| (user.uuid.clone(), user))
+            .collect::<HashMap<String, User>>();
+        Self {
+            room_name: String::new(),
+            room_uuid,
+            users,
+            created_at,
+            owner: None,
+            read_receipts: read_receipts_default(),
+            kind: RoomKind::Group,
+            roles: HashMap::new(),
+            banned: HashSet::new(),
+            public: false,
+            description: String::new(),
+            topic: String::new(),
+            archived: false,
+            version: 0,
+        }
+    }
+
+    // the same pair always gets the same uuid, whichever of the two opens the conversation
+    pub fn direct_uuid(user_uuid: &str, other_uuid: &str) -> String {
+        let (first, second) = if user_uuid <= other_uuid {
+            (user_uuid, other_uuid)
+        } else {
+            (other_uuid, user_uuid)
+        };
+        let name = format!("direct:{}:{}", first, second);
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes())
+            .as_simple()
+            .to_string()
+    }
+
+    pub fn settings(&self) -> RoomSettings {
+        RoomSettings {
+            room_name: self.room_name.clone(),
+            topic: self.topic.clone(),
+            description: self.description.clone(),
+            public: self.public,
+            archived: self.archived,
+        }
+    }
+
+    pub fn apply_settings(&mut self, settings: RoomSettings) {
+        self.room_name = settings.room_name;
+        self.topic = settings.topic;
+        self.description = settings.description;
+        self.public = settings.public;
+        self.archived = settings.archived;
+    }
+
+    // conversations are named after the other participants
+    pub fn display_name(&self, viewer_uuid: &str) -> String {
+        match self.kind {
+            RoomKind::Channel => self.room_name.clone(),
+            RoomKind::Direct => self
+                .users
+                .values()
+                .find(|user| user.uuid != viewer_uuid)
+                .map(|user| user.user_name.clone())
+                .unwrap_or_default(),
+            RoomKind::Group => self
+                .participants()
+                .into_iter()
+                .filter(|user| user.uuid != viewer_uuid)
+                .map(|user| user.user_name)
+                .collect::<Vec<String>>()
+                .join(", "),
+        }
+    }
+
+    // sorted by name so every participant sees them in the same order
+    pub fn participants(&self) -> Vec<User> {
+        let mut vec_user = self.users.values().cloned().collect::<Vec<User>>();
+        vec_user.sort_by(|a, b| a.user_name.cmp(&b.user_name));
+        vec_user
+    }
+
+    pub fn is_owner(&self, user_uuid: &str) -> bool {
+        self.role_of(user_uuid) == Some(Role::Owner)
+    }
+
+    // none for users outside the room
+    pub fn role_of(&self, user_uuid: &str) -> Option<Role> {
+        if !self.users.contains_key(user_uuid) {
+            return None;
+        }
+        let role = match self.roles.get(user_uuid) {
+            Some(role) => *role,
+            None if self.owner.as_deref() == Some(user_uuid) => Role::Owner,
+            None => Role::Member,
+        };
+        Some(role)
+    }
+
+    pub fn can(&self, user_uuid: &str, permission: Permission) -> bool {
+        self.role_of(user_uuid)
+            .is_some_and(|role| role.can(permission))
+    }
+
+    pub fn require(&self, user_uuid: &str, permission: Permission) -> Result<(), ServerError> {
+        if self.can(user_uuid, permission) {
+            Ok(())
+        } else {
+            Err(ServerError::NotPermitted(permission))
         }
     }
 
@@ -38,6 +241,7 @@ impl RoomData {
     pub fn remove_user(&mut self, user: User) -> Result<(), ServerError> {
         if self.users.contains_key(&user.uuid) {
             self.users.retain(|k, _| *k != user.uuid);
+            self.roles.remove(&user.uuid);
             Ok(())
         } else {
             Err(ServerError::UserDoesNotExist)
@@ -47,17 +251,37 @@ impl RoomData {
 
 #[cfg(feature = "ssr")]
 use {
-    crate::{models::message_model::WsPayload, state::db::Database},
-    std::sync::{Arc, RwLock},
-    tokio::sync::{broadcast, mpsc},
+    crate::{
+        models::{
+            frame_model::ServerFrame, mention_model::Mention, message_model::MsgResponse,
+            presence_model::PresenceStatus,
+        },
+        state::{presence::PresenceTracker, repository::Repository},
+    },
+    std::{
+        sync::{Arc, RwLock},
+        time::Duration,
+    },
+    tokio::{
+        sync::{broadcast, mpsc},
+        time::{sleep, Instant},
+    },
 };
 
+// a typing indicator goes away on its own unless the client refreshes it within this time
+#[cfg(feature = "ssr")]
+pub const TYPING_TTL: Duration = Duration::from_secs(6);
+
+// user uuid -> conn id -> sender, a user has one connection per open tab
+#[cfg(feature = "ssr")]
+pub type RoomSenders = HashMap<String, HashMap<String, mpsc::UnboundedSender<ServerFrame>>>;
+
 #[cfg(feature = "ssr")]
 #[derive(Debug, Clone, Default)]
 pub struct ChatRoom {
     pub uuid: String,
     pub name: String,
-    pub users: Arc<RwLock<HashMap<String, Option<mpsc::UnboundedSender<WsPayload>>>>>,
+    pub users: Arc<RwLock<RoomSenders>>,
 }
 
 #[cfg(feature = "ssr")]
@@ -65,19 +289,28 @@ impl ChatRoom {
     pub fn from_room_data(room_data: &RoomData) -> Self {
         let uuid = room_data.room_uuid.clone();
         let name = room_data.room_name.clone();
-        let users = Arc::new(RwLock::new(HashMap::<
-            String,
-            Option<mpsc::UnboundedSender<WsPayload>>,
-        >::new()));
+        let users = Arc::new(RwLock::new(RoomSenders::new()));
         Self { uuid, name, users }
     }
 }
 
+// ---- tells the connections of `user_uuid` to start listening to the room
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone)]
+pub struct RoomJoined {
+    pub room_data: RoomData,
+    pub user_uuid: String,
+}
+
 #[cfg(feature = "ssr")]
 #[derive(Debug, Clone)]
 pub struct RoomsManager {
     pub chatrooms: Arc<RwLock<HashMap<String, ChatRoom>>>,
-    pub ipc_sender: broadcast::Sender<ChatRoom>,
+    pub ipc_sender: broadcast::Sender<RoomJoined>,
+    pub presence: PresenceTracker,
+    // (room uuid, user uuid) -> when the typing indicator expires
+    typing: Arc<RwLock<HashMap<(String, String), Instant>>>,
 }
 
 #[cfg(feature = "ssr")]
@@ -85,91 +318,722 @@ impl RoomsManager {
     pub fn init() -> Self {
         let chatrooms = Arc::new(RwLock::new(HashMap::<String, ChatRoom>::new()));
         let (ipc_sender, _) = broadcast::channel(1024);
+        let typing = Arc::new(RwLock::new(HashMap::<(String, String), Instant>::new()));
         Self {
             chatrooms,
             ipc_sender,
+            presence: PresenceTracker::default(),
+            typing,
+        }
+    }
+
+    // --- live rooms only hold the senders of connected members
+
+    pub fn attach(
+        &self,
+        room_data: &RoomData,
+        user_uuid: &str,
+        conn_id: &str,
+        sender: mpsc::UnboundedSender<ServerFrame>,
+    ) {
+        let mut chatrooms = self.chatrooms.write().unwrap();
+        let chatroom = chatrooms
+            .entry(room_data.room_uuid.clone())
+            .or_insert_with(|| ChatRoom::from_room_data(room_data));
+        let mut users = chatroom.users.write().unwrap();
+        users
+            .entry(user_uuid.to_string())
+            .or_default()
+            .insert(conn_id.to_string(), sender);
+    }
+
+    // drops the connection from every room it was attached to, the user's other tabs stay
+    pub fn detach(&self, user_uuid: &str, conn_id: &str) {
+        let chatrooms = self.chatrooms.read().unwrap();
+        for chatroom in chatrooms.values() {
+            let mut users = chatroom.users.write().unwrap();
+            if let Some(connections) = users.get_mut(user_uuid) {
+                connections.remove(conn_id);
+                if connections.is_empty() {
+                    users.remove(user_uuid);
+                }
+            }
         }
     }
 
+    fn rooms_of(&self, user_uuid: &str) -> Vec<String> {
+        let chatrooms = self.chatrooms.read().unwrap();
+        chatrooms
+            .values()
+            .filter(|chatroom| chatroom.users.read().unwrap().contains_key(user_uuid))
+            .map(|chatroom| chatroom.uuid.clone())
+            .collect()
+    }
+
+    // --- presence, changes are told to every room the user is attached to
+
+    pub fn connect(&self, user_uuid: &str, conn_id: &str) {
+        if let Some(status) = self.presence.connect(user_uuid, conn_id) {
+            self.broadcast_presence(user_uuid, status);
+        }
+    }
+
+    pub fn heartbeat(&self, user_uuid: &str, conn_id: &str, idle: bool) {
+        if let Some(status) = self.presence.heartbeat(user_uuid, conn_id, idle) {
+            self.broadcast_presence(user_uuid, status);
+        }
+    }
+
+    pub fn disconnect(&self, user_uuid: &str, conn_id: &str) {
+        if let Some(status) = self.presence.disconnect(user_uuid, conn_id) {
+            self.broadcast_presence(user_uuid, status);
+        }
+        self.detach(user_uuid, conn_id);
+    }
+
+    fn broadcast_presence(&self, user_uuid: &str, status: PresenceStatus) {
+        for room_uuid in self.rooms_of(user_uuid) {
+            self.broadcast_except(
+                &room_uuid,
+                user_uuid,
+                ServerFrame::Presence {
+                    user_uuid: user_uuid.to_string(),
+                    status,
+                },
+            );
+        }
+    }
+
+    pub fn broadcast(&self, room_uuid: &str, server_frame: ServerFrame) {
+        let chatrooms = self.chatrooms.read().unwrap();
+        if let Some(chatroom) = chatrooms.get(room_uuid) {
+            let users = chatroom.users.read().unwrap();
+            for tx in users.values().flat_map(HashMap::values) {
+                let _ = tx.send(server_frame.clone());
+            }
+        }
+    }
+
+    pub fn broadcast_except(&self, room_uuid: &str, user_uuid: &str, server_frame: ServerFrame) {
+        let chatrooms = self.chatrooms.read().unwrap();
+        if let Some(chatroom) = chatrooms.get(room_uuid) {
+            let users = chatroom.users.read().unwrap();
+            let others = users
+                .iter()
+                .filter(|(uuid, _)| *uuid != user_uuid)
+                .flat_map(|(_, connections)| connections.values());
+            for tx in others {
+                let _ = tx.send(server_frame.clone());
+            }
+        }
+    }
+
+    // --- mentions reach every connection of the mentioned members, whatever room they are looking at
+
+    pub fn notify_mentions<'a>(
+        &self,
+        msg: &MsgResponse,
+        mentions: impl IntoIterator<Item = &'a Mention>,
+    ) {
+        let chatrooms = self.chatrooms.read().unwrap();
+        let Some(chatroom) = chatrooms.get(&msg.channel) else {
+            return;
+        };
+        let users = chatroom.users.read().unwrap();
+        let sender_uuid = msg.msg_sender.as_ref().map(|sender| sender.uuid.as_str());
+        for mention in mentions {
+            if Some(mention.user_uuid.as_str()) == sender_uuid {
+                continue;
+            }
+            for tx in users
+                .get(&mention.user_uuid)
+                .into_iter()
+                .flat_map(HashMap::values)
+            {
+                let _ = tx.send(ServerFrame::Mention { msg: msg.clone() });
+            }
+        }
+    }
+
+    // --- typing indicators, only the transitions are broadcast, refreshes just push the expiry back
+
+    pub fn start_typing(&self, room_uuid: &str, user: &User) {
+        let key = (room_uuid.to_string(), user.uuid.clone());
+        let was_typing = {
+            let mut typing = self.typing.write().unwrap();
+            typing
+                .insert(key.clone(), Instant::now() + TYPING_TTL)
+                .is_some()
+        };
+        if !was_typing {
+            self.broadcast_typing(room_uuid, user, true);
+        }
+
+        let rooms_manager = self.clone();
+        let user = user.clone();
+        tokio::spawn(async move {
+            sleep(TYPING_TTL).await;
+            let expired = {
+                let mut typing = rooms_manager.typing.write().unwrap();
+                match typing.get(&key) {
+                    Some(expires_at) if *expires_at <= Instant::now() => {
+                        typing.remove(&key);
+                        true
+                    }
+                    _ => false,
+                }
+            };
+            if expired {
+                rooms_manager.broadcast_typing(&key.0, &user, false);
+            }
+        });
+    }
+
+    pub fn stop_typing(&self, room_uuid: &str, user: &User) {
+        let was_typing = {
+            let mut typing = self.typing.write().unwrap();
+            typing
+                .remove(&(room_uuid.to_string(), user.uuid.clone()))
+                .is_some()
+        };
+        if was_typing {
+            self.broadcast_typing(room_uuid, user, false);
+        }
+    }
+
+    // used when the user disconnects
+    pub fn stop_all_typing(&self, user: &User) {
+        let room_uuids = {
+            let mut typing = self.typing.write().unwrap();
+            let room_uuids = typing
+                .keys()
+                .filter(|(_, user_uuid)| *user_uuid == user.uuid)
+                .map(|(room_uuid, _)| room_uuid.clone())
+                .collect::<Vec<String>>();
+            typing.retain(|(_, user_uuid), _| *user_uuid != user.uuid);
+            room_uuids
+        };
+        for room_uuid in room_uuids {
+            self.broadcast_typing(&room_uuid, user, false);
+        }
+    }
+
+    fn broadcast_typing(&self, room_uuid: &str, user: &User, active: bool) {
+        self.broadcast_except(
+            room_uuid,
+            &user.uuid,
+            ServerFrame::Typing {
+                room_uuid: room_uuid.to_string(),
+                user: user.clone(),
+                active,
+            },
+        );
+    }
+
     pub async fn new_room(
         &self,
         room_name: String,
+        description: String,
+        public: bool,
         user: User,
-        pool: &Database,
+        repository: &Repository,
         created_at: DateTime<Utc>,
     ) -> Result<String, ServerError> {
-        let mut room_data = RoomData::new(room_name, created_at);
+        // checked by the same rules as editing the settings later
+        let settings = RoomSettings {
+            room_name,
+            description,
+            public,
+            ..Default::default()
+        }
+        .trimmed();
+        settings.validate()?;
+        let mut room_data = RoomData::new(settings.room_name, user.uuid.clone(), created_at);
+        room_data.description = settings.description;
+        room_data.public = settings.public;
+        let user_uuid = user.uuid.clone();
         room_data.insert_user(user)?;
+        room_data.roles.insert(user_uuid.clone(), Role::Owner);
         let room_uuid = room_data.room_uuid.clone();
-        {
-            let chatroom = ChatRoom::from_room_data(&room_data);
-            self.ipc_sender
-                .send(chatroom)
-                .map_err(|_| ServerError::IPCFailed)?;
-        }
-        pool.client
-            .create::<Option<RoomData>>(("room_data", &room_data.room_uuid))
-            .content(room_data)
-            .await?;
+        repository.rooms.insert_room(&room_data).await?;
+        // sending only fails when nobody is connected, then there is no one to notify
+        let _ = self.ipc_sender.send(RoomJoined {
+            room_data,
+            user_uuid,
+        });
+        Ok(room_uuid)
+    }
+
+    // finds the direct conversation between the two users, starting it the first time
+    pub async fn open_direct(
+        &self,
+        user: User,
+        other: User,
+        repository: &Repository,
+        created_at: DateTime<Utc>,
+    ) -> Result<String, ServerError> {
+        if user.uuid == other.uuid {
+            return Err(ServerError::DirectWithSelf);
+        }
+        let room_uuid = RoomData::direct_uuid(&user.uuid, &other.uuid);
+        if repository.rooms.get_room(&room_uuid).await?.is_some() {
+            return Ok(room_uuid);
+        }
+        let room_data = RoomData::new_direct(user, other, created_at);
+        // both users may open it at the same time, the second insert then finds the first one
+        if let Err(err) = repository.rooms.insert_room(&room_data).await {
+            return match repository.rooms.get_room(&room_uuid).await? {
+                Some(_) => Ok(room_uuid),
+                None => Err(err),
+            };
+        }
+        for user_uuid in room_data.users.keys() {
+            repository.users.add_channel(user_uuid, &room_uuid).await?;
+            let _ = self.ipc_sender.send(RoomJoined {
+                room_data: room_data.clone(),
+                user_uuid: user_uuid.clone(),
+            });
+        }
         Ok(room_uuid)
     }
 
+    pub async fn new_group(
+        &self,
+        user: User,
+        others: Vec<User>,
+        repository: &Repository,
+        created_at: DateTime<Utc>,
+    ) -> Result<String, ServerError> {
+        let mut vec_user = vec![user];
+        for other in others {
+            if !vec_user.contains(&other) {
+                vec_user.push(other);
+            }
+        }
+        if !(GROUP_MIN..=GROUP_MAX).contains(&vec_user.len()) {
+            return Err(ServerError::GroupSize);
+        }
+        let room_data = RoomData::new_group(vec_user, created_at);
+        let room_uuid = room_data.room_uuid.clone();
+        repository.rooms.insert_room(&room_data).await?;
+        for user_uuid in room_data.users.keys() {
+            repository.users.add_channel(user_uuid, &room_uuid).await?;
+            let _ = self.ipc_sender.send(RoomJoined {
+                room_data: room_data.clone(),
+                user_uuid: user_uuid.clone(),
+            });
+        }
+        Ok(room_uuid)
+    }
+
+    // any participant may bring others in, as long as the group stays small
+    pub async fn add_to_group(
+        &self,
+        room_uuid: &str,
+        user: &User,
+        new_users: Vec<User>,
+        repository: &Repository,
+    ) -> Result<(), ServerError> {
+        let mut added = Vec::<User>::new();
+        let room_data = self
+            .modify_room(room_uuid, repository, |room_data| {
+                if room_data.kind != RoomKind::Group {
+                    return Err(ServerError::NotAGroup);
+                }
+                if !room_data.users.contains_key(&user.uuid) {
+                    return Err(ServerError::PrivateConversation);
+                }
+                room_data.require(&user.uuid, Permission::Invite)?;
+                added = new_users
+                    .iter()
+                    .filter(|new_user| !room_data.users.contains_key(&new_user.uuid))
+                    .cloned()
+                    .collect();
+                for new_user in added.iter() {
+                    room_data
+                        .users
+                        .insert(new_user.uuid.clone(), new_user.clone());
+                }
+                if room_data.users.len() > GROUP_MAX {
+                    return Err(ServerError::GroupSize);
+                }
+                Ok(!added.is_empty())
+            })
+            .await?;
+        let Some(room_data) = room_data else {
+            return Ok(());
+        };
+        for new_user in added.iter() {
+            repository
+                .users
+                .add_channel(&new_user.uuid, room_uuid)
+                .await?;
+            let _ = self.ipc_sender.send(RoomJoined {
+                room_data: room_data.clone(),
+                user_uuid: new_user.uuid.clone(),
+            });
+        }
+        self.broadcast(
+            room_uuid,
+            ServerFrame::ParticipantsChanged {
+                room_uuid: room_uuid.to_string(),
+                users: room_data.participants(),
+            },
+        );
+        Ok(())
+    }
+
+    // the owner hands out and takes back the admin role, the owner role itself never moves
+    pub async fn set_role(
+        &self,
+        room_uuid: &str,
+        user: &User,
+        target_uuid: &str,
+        role: Role,
+        repository: &Repository,
+    ) -> Result<(), ServerError> {
+        let changed = self
+            .modify_room(room_uuid, repository, |room_data| {
+                if !room_data.is_owner(&user.uuid) || role == Role::Owner {
+                    return Err(ServerError::NotRoomOwner);
+                }
+                match room_data.role_of(target_uuid) {
+                    None => return Err(ServerError::UserDoesNotExist),
+                    Some(Role::Owner) => return Err(ServerError::NotRoomOwner),
+                    Some(current) if current == role => return Ok(false),
+                    Some(_) => {}
+                }
+                room_data.roles.insert(target_uuid.to_string(), role);
+                Ok(true)
+            })
+            .await?;
+        if changed.is_none() {
+            return Ok(());
+        }
+        self.broadcast(
+            room_uuid,
+            ServerFrame::RoleChanged {
+                room_uuid: room_uuid.to_string(),
+                user_uuid: target_uuid.to_string(),
+                role,
+            },
+        );
+        Ok(())
+    }
+
     pub async fn join_room(
         &self,
         room_uuid: &str,
         user: User,
-        pool: &Database,
+        repository: &Repository,
     ) -> Result<(), ServerError> {
-        let find_entry = pool
-            .client
-            .select::<Option<RoomData>>(("room_data", room_uuid))
+        let room_data = self
+            .modify_room(room_uuid, repository, |room_data| {
+                if room_data.kind != RoomKind::Channel {
+                    return Err(ServerError::PrivateConversation);
+                }
+                if room_data.banned.contains(&user.uuid) {
+                    return Err(ServerError::BannedFromRoom);
+                }
+                if room_data.archived {
+                    return Err(ServerError::RoomArchived);
+                }
+                room_data.insert_user(user.clone())?;
+                Ok(true)
+            })
             .await?;
-        if let Some(mut room_data) = find_entry {
-            room_data.insert_user(user)?;
-            pool.client
-                .update::<Option<RoomData>>(("room_data", &room_data.room_uuid))
-                .merge(room_data.clone())
-                .await?;
-            {
-                let chatrooms = self.chatrooms.read().unwrap();
-                let chatroom = chatrooms.get(&room_data.room_uuid).unwrap();
-                self.ipc_sender
-                    .send(chatroom.clone())
-                    .map_err(|_| ServerError::IPCFailed)?;
+        if let Some(room_data) = room_data {
+            let _ = self.ipc_sender.send(RoomJoined {
+                room_data,
+                user_uuid: user.uuid,
+            });
+        }
+        Ok(())
+    }
+
+    // ---- settings, only channels have any since conversations
+    // ---- have no moderators to change them
+
+    pub async fn update_settings(
+        &self,
+        room_uuid: &str,
+        user: &User,
+        settings: RoomSettings,
+        repository: &Repository,
+    ) -> Result<(), ServerError> {
+        let settings = settings.trimmed();
+        settings.validate()?;
+        self.modify_room(room_uuid, repository, |room_data| {
+            if room_data.kind != RoomKind::Channel {
+                return Err(ServerError::PrivateConversation);
+            }
+            room_data.require(&user.uuid, Permission::EditSettings)?;
+            room_data.apply_settings(settings.clone());
+            Ok(true)
+        })
+        .await?;
+        if let Some(chatroom) = self.chatrooms.write().unwrap().get_mut(room_uuid) {
+            chatroom.name = settings.room_name.clone();
+        }
+        self.broadcast(
+            room_uuid,
+            ServerFrame::RoomUpdated {
+                room_uuid: room_uuid.to_string(),
+                settings,
+            },
+        );
+        Ok(())
+    }
+
+    // the members hear about it first, then the room, its messages, invites
+    // and read markers are gone for good
+    pub async fn delete_room(
+        &self,
+        room_uuid: &str,
+        user: &User,
+        repository: &Repository,
+    ) -> Result<(), ServerError> {
+        let room_data = self.validate_uuid(room_uuid, repository).await?;
+        room_data.require(&user.uuid, Permission::DeleteRoom)?;
+
+        for user_uuid in room_data.users.keys() {
+            // a member whose list already lost the room is fine, it's going away anyway
+            match repository.users.remove_channel(user_uuid, room_uuid).await {
+                Ok(()) | Err(ServerError::RemoveChannelError) => {}
+                Err(err) => return Err(err),
             }
         }
+        repository.messages.delete_room_msgs(room_uuid).await?;
+        repository.rooms.delete_room(room_uuid).await?;
+        self.broadcast(
+            room_uuid,
+            ServerFrame::RoomDeleted {
+                room_uuid: room_uuid.to_string(),
+            },
+        );
+        self.chatrooms.write().unwrap().remove(room_uuid);
         Ok(())
     }
 
+    // public rooms let anyone in straight from the directory
+    pub async fn join_public(
+        &self,
+        room_uuid: &str,
+        user: User,
+        repository: &Repository,
+    ) -> Result<(), ServerError> {
+        let room_data = self.validate_uuid(room_uuid, repository).await?;
+        if room_data.kind != RoomKind::Channel || !room_data.public {
+            return Err(ServerError::NotPublic);
+        }
+        self.join_room(room_uuid, user, repository).await
+    }
+
+    // ---- invites, the way into private channels
+
+    pub async fn create_invite(
+        &self,
+        room_uuid: &str,
+        user: &User,
+        expires_at: Option<DateTime<Utc>>,
+        max_uses: Option<u32>,
+        repository: &Repository,
+        created_at: DateTime<Utc>,
+    ) -> Result<Invite, ServerError> {
+        let room_data = self.validate_uuid(room_uuid, repository).await?;
+        if room_data.kind != RoomKind::Channel {
+            return Err(ServerError::PrivateConversation);
+        }
+        room_data.require(&user.uuid, Permission::Invite)?;
+        let invite = Invite::new(
+            room_uuid.to_string(),
+            user.uuid.clone(),
+            created_at,
+            expires_at,
+            max_uses,
+        );
+        repository.invites.insert_invite(&invite).await?;
+        Ok(invite)
+    }
+
+    // the creator can take back their own invite, moderators any invite of the room
+    pub async fn revoke_invite(
+        &self,
+        code: &str,
+        user: &User,
+        repository: &Repository,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<(), ServerError> {
+        let invite = repository
+            .invites
+            .get_invite(code)
+            .await?
+            .ok_or(ServerError::InvalidInvite)?;
+        if invite.created_by != user.uuid {
+            self.validate_uuid(&invite.room_uuid, repository)
+                .await?
+                .require(&user.uuid, Permission::EditSettings)?;
+        }
+        repository.invites.revoke_invite(code, revoked_at).await
+    }
+
+    // joins the room behind the invite and returns its uuid, members that are
+    // already in the room don't use the invite up
+    pub async fn join_with_invite(
+        &self,
+        code: &str,
+        user: User,
+        repository: &Repository,
+        now: DateTime<Utc>,
+    ) -> Result<String, ServerError> {
+        let invite = repository
+            .invites
+            .get_invite(code)
+            .await?
+            .ok_or(ServerError::InvalidInvite)?;
+        let room_data = self.validate_uuid(&invite.room_uuid, repository).await?;
+        if room_data.users.contains_key(&user.uuid) {
+            return Ok(invite.room_uuid);
+        }
+        if room_data.banned.contains(&user.uuid) {
+            return Err(ServerError::BannedFromRoom);
+        }
+        if room_data.archived {
+            return Err(ServerError::RoomArchived);
+        }
+        if !repository.invites.redeem_invite(code, now).await? {
+            return Err(ServerError::InvalidInvite);
+        }
+        self.join_room(&invite.room_uuid, user, repository).await?;
+        Ok(invite.room_uuid)
+    }
+
     pub async fn leave_room(
         &self,
         room_uuid: &str,
         user: User,
-        pool: &Database,
+        repository: &Repository,
+    ) -> Result<(), ServerError> {
+        // the room would be left without anyone to run it, the owner deletes it instead
+        self.remove_member(room_uuid, &user.uuid, false, repository, |room_data| {
+            if room_data.is_owner(&user.uuid) {
+                return Err(ServerError::OwnerCannotLeave);
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    // kicks `target_uuid` out of the room, a ban also keeps them from joining again
+    pub async fn kick(
+        &self,
+        room_uuid: &str,
+        user: &User,
+        target_uuid: &str,
+        ban: bool,
+        repository: &Repository,
+    ) -> Result<(), ServerError> {
+        self.remove_member(room_uuid, target_uuid, ban, repository, |room_data| {
+            room_data.require(&user.uuid, Permission::Kick)?;
+            let user_role = room_data.role_of(&user.uuid).unwrap_or_default();
+            match room_data.role_of(target_uuid) {
+                None => Err(ServerError::UserDoesNotExist),
+                Some(target_role) if target_role <= user_role => {
+                    Err(ServerError::CannotRemoveMember)
+                }
+                Some(_) => Ok(()),
+            }
+        })
+        .await
+    }
+
+    // `authorize` checks the removal against the same copy of the room that gets written
+    async fn remove_member(
+        &self,
+        room_uuid: &str,
+        user_uuid: &str,
+        ban: bool,
+        repository: &Repository,
+        authorize: impl Fn(&RoomData) -> Result<(), ServerError>,
     ) -> Result<(), ServerError> {
-        let find_entry = pool
-            .client
-            .select::<Option<RoomData>>(("room_data", room_uuid))
+        let mut removed = None;
+        let room_data = self
+            .modify_room(room_uuid, repository, |room_data| {
+                authorize(room_data)?;
+                let user = room_data
+                    .users
+                    .get(user_uuid)
+                    .cloned()
+                    .ok_or(ServerError::UserDoesNotExist)?;
+                room_data.remove_user(user.clone())?;
+                if ban {
+                    room_data.banned.insert(user.uuid.clone());
+                }
+                removed = Some(user);
+                Ok(true)
+            })
             .await?;
-        if let Some(mut room_data) = find_entry {
-            room_data.remove_user(user)?;
-            pool.client
-                .update::<Option<RoomData>>(("room_data", &room_data.room_uuid))
-                .merge(room_data)
-                .await?;
+        let (Some(room_data), Some(user)) = (room_data, removed) else {
+            return Ok(());
+        };
+        repository
+            .users
+            .remove_channel(&user.uuid, &room_data.room_uuid)
+            .await?;
+
+        // --- everyone attached hears about it, the removed user included, then all their senders are dropped
+        let room_uuid = room_data.room_uuid.as_str();
+        self.stop_typing(room_uuid, &user);
+        self.broadcast(
+            room_uuid,
+            ServerFrame::MemberRemoved {
+                room_uuid: room_uuid.to_string(),
+                user_uuid: user.uuid.clone(),
+                banned: ban,
+            },
+        );
+        if let Some(chatroom) = self.chatrooms.read().unwrap().get(room_uuid) {
+            chatroom.users.write().unwrap().remove(&user.uuid);
+        }
+        if room_data.kind == RoomKind::Group {
+            self.broadcast(
+                room_uuid,
+                ServerFrame::ParticipantsChanged {
+                    room_uuid: room_uuid.to_string(),
+                    users: room_data.participants(),
+                },
+            );
         }
         Ok(())
     }
 
+    // a write only lands when nobody else wrote the room since it was read, otherwise `change`
+    // runs again on a fresh copy, it returns false when there is nothing to write
+
+    pub async fn modify_room(
+        &self,
+        room_uuid: &str,
+        repository: &Repository,
+        mut change: impl FnMut(&mut RoomData) -> Result<bool, ServerError>,
+    ) -> Result<Option<RoomData>, ServerError> {
+        loop {
+            let mut room_data = self.validate_uuid(room_uuid, repository).await?;
+            if !change(&mut room_data)? {
+                return Ok(None);
+            }
+            if repository.rooms.update_room(&room_data).await? {
+                room_data.version += 1;
+                return Ok(Some(room_data));
+            }
+        }
+    }
+
     pub async fn get_room_name(
         &self,
         room_uuid: &str,
-        pool: &Database,
+        repository: &Repository,
     ) -> Result<String, ServerError> {
-        let find_entry = pool
-            .client
-            .select::<Option<RoomData>>(("room_data", room_uuid))
-            .await?;
+        let find_entry = repository.rooms.get_room(room_uuid).await?;
         match find_entry {
             Some(room_data) => Ok(room_data.room_name),
             None => Err(ServerError::RoomDoesNotExist),
@@ -179,15 +1043,436 @@ impl RoomsManager {
     pub async fn validate_uuid(
         &self,
         room_uuid: &str,
-        pool: &Database,
+        repository: &Repository,
     ) -> Result<RoomData, ServerError> {
-        let find_entry = pool
-            .client
-            .select::<Option<RoomData>>(("room_data", room_uuid))
-            .await?;
+        let find_entry = repository.rooms.get_room(room_uuid).await?;
         match find_entry {
             Some(room_data) => Ok(room_data),
             None => Err(ServerError::RoomDoesNotExist),
         }
     }
+
+    // the room as long as `user_uuid` is in it, conversations don't tell outsiders more than that
+    pub async fn member_room(
+        &self,
+        room_uuid: &str,
+        user_uuid: &str,
+        repository: &Repository,
+    ) -> Result<RoomData, ServerError> {
+        let room_data = self.validate_uuid(room_uuid, repository).await?;
+        if room_data.users.contains_key(user_uuid) {
+            Ok(room_data)
+        } else if room_data.kind.is_conversation() {
+            Err(ServerError::PrivateConversation)
+        } else {
+            Err(ServerError::NotAMember)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use {
+        super::*,
+        crate::models::{
+            message_model::MsgCursor, read_marker_model::ReadMarker, user_model::UserData,
+        },
+    };
+
+    async fn new_user(repository: &Repository, user_name: &str) -> User {
+        let user_data = UserData::new(
+            Uuid::new_v4().as_simple().to_string(),
+            user_name.to_string(),
+            format!("{}@example.com", user_name),
+            String::new(),
+            Utc::now(),
+        );
+        repository.users.insert_user(&user_data).await.unwrap();
+        User::from_user_data(&user_data)
+    }
+
+    // what the server functions do around the rooms manager, the user's list follows the room
+    async fn new_room(
+        rooms_manager: &RoomsManager,
+        repository: &Repository,
+        owner: &User,
+    ) -> String {
+        let room_uuid = rooms_manager
+            .new_room(
+                "general".to_string(),
+                String::new(),
+                false,
+                owner.clone(),
+                repository,
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+        repository
+            .users
+            .add_channel(&owner.uuid, &room_uuid)
+            .await
+            .unwrap();
+        room_uuid
+    }
+
+    async fn join(
+        rooms_manager: &RoomsManager,
+        repository: &Repository,
+        room_uuid: &str,
+        user: &User,
+    ) {
+        rooms_manager
+            .join_room(room_uuid, user.clone(), repository)
+            .await
+            .unwrap();
+        repository
+            .users
+            .add_channel(&user.uuid, room_uuid)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn new_room_is_validated_like_settings() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+
+        let blank = rooms_manager
+            .new_room(
+                "   ".to_string(),
+                String::new(),
+                false,
+                alice.clone(),
+                &repository,
+                Utc::now(),
+            )
+            .await;
+        assert!(matches!(blank, Err(ServerError::InvalidRoomSettings(_))));
+        let too_long = rooms_manager
+            .new_room(
+                "x".repeat(65),
+                String::new(),
+                false,
+                alice.clone(),
+                &repository,
+                Utc::now(),
+            )
+            .await;
+        assert!(matches!(too_long, Err(ServerError::InvalidRoomSettings(_))));
+
+        let room_uuid = rooms_manager
+            .new_room(
+                "  general ".to_string(),
+                String::new(),
+                false,
+                alice,
+                &repository,
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+        let room_data = rooms_manager
+            .validate_uuid(&room_uuid, &repository)
+            .await
+            .unwrap();
+        assert_eq!(room_data.room_name, "general");
+    }
+
+    #[tokio::test]
+    async fn only_the_owner_changes_roles() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let bob = new_user(&repository, "bob").await;
+        let carol = new_user(&repository, "carol").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        join(&rooms_manager, &repository, &room_uuid, &bob).await;
+        join(&rooms_manager, &repository, &room_uuid, &carol).await;
+
+        rooms_manager
+            .set_role(&room_uuid, &alice, &bob.uuid, Role::Admin, &repository)
+            .await
+            .unwrap();
+        let room_data = rooms_manager
+            .validate_uuid(&room_uuid, &repository)
+            .await
+            .unwrap();
+        assert_eq!(room_data.role_of(&alice.uuid), Some(Role::Owner));
+        assert_eq!(room_data.role_of(&bob.uuid), Some(Role::Admin));
+        assert_eq!(room_data.role_of(&carol.uuid), Some(Role::Member));
+
+        // admins can't hand out roles, and the owner role never moves
+        let by_admin = rooms_manager
+            .set_role(&room_uuid, &bob, &carol.uuid, Role::Admin, &repository)
+            .await;
+        assert!(matches!(by_admin, Err(ServerError::NotRoomOwner)));
+        let to_owner = rooms_manager
+            .set_role(&room_uuid, &alice, &bob.uuid, Role::Owner, &repository)
+            .await;
+        assert!(matches!(to_owner, Err(ServerError::NotRoomOwner)));
+    }
+    #[tokio::test]
+    async fn members_are_only_removed_by_those_above_them() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let bob = new_user(&repository, "bob").await;
+        let carol = new_user(&repository, "carol").await;
+        let dave = new_user(&repository, "dave").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        for user in [&bob, &carol, &dave] {
+            join(&rooms_manager, &repository, &room_uuid, user).await;
+        }
+        for admin in [&bob, &carol] {
+            rooms_manager
+                .set_role(&room_uuid, &alice, &admin.uuid, Role::Admin, &repository)
+                .await
+                .unwrap();
+        }
+
+        let by_member = rooms_manager
+            .kick(&room_uuid, &dave, &bob.uuid, false, &repository)
+            .await;
+        assert!(matches!(
+            by_member,
+            Err(ServerError::NotPermitted(Permission::Kick))
+        ));
+        let same_role = rooms_manager
+            .kick(&room_uuid, &bob, &carol.uuid, false, &repository)
+            .await;
+        assert!(matches!(same_role, Err(ServerError::CannotRemoveMember)));
+        let the_owner = rooms_manager
+            .kick(&room_uuid, &bob, &alice.uuid, false, &repository)
+            .await;
+        assert!(matches!(the_owner, Err(ServerError::CannotRemoveMember)));
+
+        rooms_manager
+            .kick(&room_uuid, &bob, &dave.uuid, false, &repository)
+            .await
+            .unwrap();
+        let room_data = rooms_manager
+            .validate_uuid(&room_uuid, &repository)
+            .await
+            .unwrap();
+        assert!(!room_data.users.contains_key(&dave.uuid));
+        // a kick without a ban leaves the door open
+        join(&rooms_manager, &repository, &room_uuid, &dave).await;
+    }
+
+    #[tokio::test]
+    async fn banned_members_stay_out() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let bob = new_user(&repository, "bob").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        join(&rooms_manager, &repository, &room_uuid, &bob).await;
+        let invite = rooms_manager
+            .create_invite(&room_uuid, &alice, None, None, &repository, Utc::now())
+            .await
+            .unwrap();
+
+        rooms_manager
+            .kick(&room_uuid, &alice, &bob.uuid, true, &repository)
+            .await
+            .unwrap();
+        let rejoin = rooms_manager
+            .join_room(&room_uuid, bob.clone(), &repository)
+            .await;
+        assert!(matches!(rejoin, Err(ServerError::BannedFromRoom)));
+        let with_invite = rooms_manager
+            .join_with_invite(&invite.code, bob, &repository, Utc::now())
+            .await;
+        assert!(matches!(with_invite, Err(ServerError::BannedFromRoom)));
+        // the refused attempt didn't use the invite up
+        let invite = repository
+            .invites
+            .get_invite(&invite.code)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(invite.uses, 0);
+    }
+
+    #[tokio::test]
+    async fn invites_run_out_and_members_reuse_them_for_free() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let bob = new_user(&repository, "bob").await;
+        let carol = new_user(&repository, "carol").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        let invite = rooms_manager
+            .create_invite(&room_uuid, &alice, None, Some(1), &repository, Utc::now())
+            .await
+            .unwrap();
+
+        let joined = rooms_manager
+            .join_with_invite(&invite.code, bob.clone(), &repository, Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(joined, room_uuid);
+        rooms_manager
+            .join_with_invite(&invite.code, bob, &repository, Utc::now())
+            .await
+            .unwrap();
+        let used_up = rooms_manager
+            .join_with_invite(&invite.code, carol, &repository, Utc::now())
+            .await;
+        assert!(matches!(used_up, Err(ServerError::InvalidInvite)));
+        let invite = repository
+            .invites
+            .get_invite(&invite.code)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(invite.uses, 1);
+    }
+
+    #[tokio::test]
+    async fn invites_are_revoked_by_their_creator_or_a_moderator() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let bob = new_user(&repository, "bob").await;
+        let carol = new_user(&repository, "carol").await;
+        let dave = new_user(&repository, "dave").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        join(&rooms_manager, &repository, &room_uuid, &bob).await;
+        join(&rooms_manager, &repository, &room_uuid, &carol).await;
+        let invite = rooms_manager
+            .create_invite(&room_uuid, &bob, None, None, &repository, Utc::now())
+            .await
+            .unwrap();
+
+        let by_member = rooms_manager
+            .revoke_invite(&invite.code, &carol, &repository, Utc::now())
+            .await;
+        assert!(matches!(
+            by_member,
+            Err(ServerError::NotPermitted(Permission::EditSettings))
+        ));
+        rooms_manager
+            .revoke_invite(&invite.code, &alice, &repository, Utc::now())
+            .await
+            .unwrap();
+        let revoked = rooms_manager
+            .join_with_invite(&invite.code, dave, &repository, Utc::now())
+            .await;
+        assert!(matches!(revoked, Err(ServerError::InvalidInvite)));
+    }
+
+    #[tokio::test]
+    async fn the_owner_stays_while_members_leave() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let bob = new_user(&repository, "bob").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        join(&rooms_manager, &repository, &room_uuid, &bob).await;
+
+        let by_owner = rooms_manager
+            .leave_room(&room_uuid, alice.clone(), &repository)
+            .await;
+        assert!(matches!(by_owner, Err(ServerError::OwnerCannotLeave)));
+        rooms_manager
+            .leave_room(&room_uuid, bob.clone(), &repository)
+            .await
+            .unwrap();
+        let room_data = rooms_manager
+            .validate_uuid(&room_uuid, &repository)
+            .await
+            .unwrap();
+        assert!(room_data.is_owner(&alice.uuid));
+        assert!(!room_data.users.contains_key(&bob.uuid));
+    }
+
+    #[tokio::test]
+    async fn stale_room_writes_are_refused() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let bob = new_user(&repository, "bob").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        let stale = rooms_manager
+            .validate_uuid(&room_uuid, &repository)
+            .await
+            .unwrap();
+        join(&rooms_manager, &repository, &room_uuid, &bob).await;
+
+        // written back whole, the copy read before the join would drop bob again
+        assert!(!repository.rooms.update_room(&stale).await.unwrap());
+        let room_data = rooms_manager
+            .validate_uuid(&room_uuid, &repository)
+            .await
+            .unwrap();
+        assert!(room_data.users.contains_key(&bob.uuid));
+    }
+
+    #[tokio::test]
+    async fn deleting_a_room_takes_its_invites_and_read_markers() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        let invite = rooms_manager
+            .create_invite(&room_uuid, &alice, None, None, &repository, Utc::now())
+            .await
+            .unwrap();
+        let cursor = MsgCursor {
+            created_at: Utc::now(),
+            msg_uuid: Uuid::new_v4().as_simple().to_string(),
+        };
+        let read_marker =
+            ReadMarker::new(alice.uuid.clone(), room_uuid.clone(), &cursor, Utc::now());
+        repository
+            .read_markers
+            .upsert_read_marker(&read_marker)
+            .await
+            .unwrap();
+
+        rooms_manager
+            .delete_room(&room_uuid, &alice, &repository)
+            .await
+            .unwrap();
+        assert!(repository
+            .rooms
+            .get_room(&room_uuid)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository
+            .invites
+            .get_invite(&invite.code)
+            .await
+            .unwrap()
+            .is_none());
+        let read_marker = repository
+            .read_markers
+            .get_read_marker(&alice.uuid, &room_uuid)
+            .await
+            .unwrap();
+        assert!(read_marker.is_none());
+    }
+
+    #[tokio::test]
+    async fn every_connection_of_a_user_gets_room_frames() {
+        let (rooms_manager, repository) = (RoomsManager::init(), Repository::memory());
+        let alice = new_user(&repository, "alice").await;
+        let room_uuid = new_room(&rooms_manager, &repository, &alice).await;
+        let room_data = rooms_manager
+            .validate_uuid(&room_uuid, &repository)
+            .await
+            .unwrap();
+        let (first_tx, mut first_rx) = mpsc::unbounded_channel::<ServerFrame>();
+        let (second_tx, mut second_rx) = mpsc::unbounded_channel::<ServerFrame>();
+        rooms_manager.attach(&room_data, &alice.uuid, "first", first_tx);
+        rooms_manager.attach(&room_data, &alice.uuid, "second", second_tx);
+
+        let deleted = ServerFrame::RoomDeleted {
+            room_uuid: room_uuid.clone(),
+        };
+        rooms_manager.broadcast(&room_uuid, deleted.clone());
+        assert!(first_rx.try_recv().is_ok());
+        assert!(second_rx.try_recv().is_ok());
+
+        // closing one tab leaves the other attached
+        rooms_manager.detach(&alice.uuid, "first");
+        rooms_manager.broadcast(&room_uuid, deleted);
+        assert!(first_rx.try_recv().is_err());
+        assert!(second_rx.try_recv().is_ok());
+    }
 }